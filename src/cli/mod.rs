@@ -0,0 +1,154 @@
+use std::{
+    fs,
+    io::{self, Read},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+/// Minimal argument parser for the per-day subcommands.
+///
+/// Options listed as `valued` take the next argument (or `--name=value`),
+/// options listed as `flags` are plain switches, anything else starting
+/// with `--` is rejected.
+pub(crate) struct Args {
+    positional: Vec<String>,
+    flags: Vec<String>,
+    values: Vec<(String, String)>,
+}
+
+impl Args {
+    pub fn parse(args: &[String], flags: &[&str], valued: &[&str]) -> Result<Self, String> {
+        let mut parsed = Args {
+            positional: vec![],
+            flags: vec![],
+            values: vec![],
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                parsed.positional.push(arg.clone());
+                continue;
+            };
+            let (name, inline_value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+            if flags.contains(&name) && inline_value.is_none() {
+                parsed.flags.push(name.to_string());
+            } else if valued.contains(&name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .cloned()
+                        .ok_or(format!("Option --{name} needs a value"))?,
+                };
+                parsed.values.push((name.to_string(), value));
+            } else {
+                return Err(format!("Unknown option --{name}"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    /// Last value given for the option, so later arguments override earlier ones.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.value(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("Invalid value `{v}` for --{name}"))
+            })
+            .transpose()
+    }
+
+    /// Range option written as `a..b` or `a..=b`.
+    pub fn range(&self, name: &str) -> Result<Option<RangeInclusive<usize>>, String> {
+        self.value(name)
+            .map(|v| {
+                parse_range(v).ok_or(format!(
+                    "Invalid range `{v}` for --{name}, expected a..b or a..=b"
+                ))
+            })
+            .transpose()
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    /// Reads the file given by `--input` (`-` for stdin), defaulting to the
+    /// puzzle input of the day.
+    pub fn input(&self, day: usize) -> Result<String, String> {
+        match self.value("input") {
+            Some("-") => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|e| format!("Cannot read stdin: {e}"))?;
+                Ok(input)
+            }
+            Some(path) => fs::read_to_string(path).map_err(|e| format!("Cannot read {path}: {e}")),
+            None => fs::read_to_string(format!("input/day{day}/input"))
+                .map_err(|e| format!("Cannot read input of day {day}: {e}")),
+        }
+    }
+}
+
+fn parse_range(s: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = s.split_once("..")?;
+    let start = start.parse().ok()?;
+    let end = match end.strip_prefix('=') {
+        Some(end) => end.parse().ok()?,
+        None => end.parse::<usize>().ok()?.checked_sub(1)?,
+    };
+    Some(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = Args::parse(
+            &args(&["report", "--plain", "--rows", "1..3", "--cols=0..5"]),
+            &["plain"],
+            &["rows", "cols"],
+        )
+        .unwrap();
+        assert_eq!(parsed.positional(), ["report"]);
+        assert!(parsed.flag("plain"));
+        assert_eq!(parsed.value("rows"), Some("1..3"));
+        assert_eq!(parsed.value("cols"), Some("0..5"));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("1..3"), Some(1..=2));
+        assert_eq!(parse_range("1..=3"), Some(1..=3));
+        assert_eq!(parse_range("1..0"), None);
+        assert_eq!(parse_range("1-3"), None);
+    }
+
+    #[test]
+    fn test_parse_args_rejects_unknown() {
+        assert!(Args::parse(&args(&["--nope"]), &[], &[]).is_err());
+        assert!(Args::parse(&args(&["--rows"]), &[], &["rows"]).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+use super::stream::{CalibrationStream, Decoder};
+use crate::cli::Args;
+
+const USAGE: &str = "Usage: day1 stream [--spelled] [--lines] [--input FILE]
+  Decodes a calibration document line by line, reading stdin by default.
+  --spelled  also accept spelled out digits (part two rule)
+  --lines    print the value and running total of every line";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &["spelled", "lines"], &["input"])?;
+    match args.positional() {
+        [command] if command == "stream" => stream(&args),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn stream(args: &Args) -> Result<(), String> {
+    let decoder = if args.flag("spelled") {
+        Decoder::Spelled
+    } else {
+        Decoder::Digits
+    };
+    let reader: Box<dyn BufRead> = match args.value("input") {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).map_err(|e| format!("Cannot open {path}: {e}"))?,
+        )),
+    };
+
+    let mut total = 0;
+    for calibration in CalibrationStream::new(reader, decoder) {
+        let calibration = calibration.map_err(|e| e.to_string())?;
+        if args.flag("lines") {
+            println!(
+                "{}: {} (total {})",
+                calibration.line, calibration.value, calibration.total
+            );
+        }
+        total = calibration.total;
+    }
+    println!("{total}");
+    Ok(())
+}
@@ -1,3 +1,6 @@
+pub(crate) mod cli;
+pub(crate) mod stream;
+
 pub(crate) mod part1 {
     fn parse_digits(input: &str) -> Vec<u32> {
         input
@@ -7,14 +10,17 @@ pub(crate) mod part1 {
             .collect()
     }
 
+    pub fn calibration_value(line: &str) -> Option<u32> {
+        let numbers = parse_digits(line);
+        let first = numbers.first()?;
+        let last = numbers.last().unwrap_or(first);
+        Some(first * 10 + last)
+    }
+
     pub fn resolve(input: &str) -> String {
-        let digits = input.lines().map(parse_digits);
         let mut sum = 0u32;
-        for numbers in digits {
-            let first = numbers.first().unwrap();
-            let last = numbers.last().unwrap_or(first);
-            let line_total = first * 10 + last;
-            sum += line_total;
+        for line in input.lines() {
+            sum += calibration_value(line).unwrap();
         }
         sum.to_string()
     }
@@ -35,40 +41,33 @@ pub(crate) mod part1 {
 }
 
 pub(crate) mod part2 {
+    const NUMS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
 
-    pub fn resolve(input: &str) -> String {
-        let nums: Vec<(usize, &str)> = vec![
-            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-        ]
-        .into_iter()
-        .enumerate()
-        .map(|(n, word)| (n + 1, word))
-        .collect();
+    pub fn calibration_value(line: &str) -> Option<u32> {
+        let mut numbers_at_line = vec![];
+        line.chars().fold("".to_string(), |mut acc, c| {
+            acc.push(c);
+            if let Some(num) = c.to_digit(10) {
+                numbers_at_line.push(num);
+            } else if let Some(num) = NUMS.iter().position(|word| acc.ends_with(word)) {
+                numbers_at_line.push(num as u32 + 1);
+            };
+            acc
+        });
+        let first = numbers_at_line.first()?;
+        let last = numbers_at_line.last().unwrap_or(first);
+        Some(first * 10 + last)
+    }
 
+    pub fn resolve(input: &str) -> String {
         input
             .lines()
             .map(|line| {
-                let mut numbers_at_line = vec![];
-                line.chars().fold("".to_string(), |mut acc, c| {
-                    acc.push(c);
-                    if let Some(num) = c.to_digit(10) {
-                        numbers_at_line.push(num as usize);
-                    } else if let Some(num) = nums
-                        .iter()
-                        .find(|(_, word)| acc.ends_with(word))
-                        .map(|(n, _)| n)
-                    {
-                        numbers_at_line.push(*num);
-                    };
-                    acc
-                });
-                let first = numbers_at_line
-                    .first()
-                    .expect("Wrong file format, cannot find any number on line");
-                let last = numbers_at_line.last().unwrap_or(first);
-                first * 10 + last
+                calibration_value(line).expect("Wrong file format, cannot find any number on line")
             })
-            .sum::<usize>()
+            .sum::<u32>()
             .to_string()
     }
 
@@ -0,0 +1,135 @@
+use std::{fmt::Display, io::BufRead};
+
+use super::{part1, part2};
+
+/// Which puzzle rule is used to find the digits of a line.
+#[derive(Debug, Clone, Copy)]
+pub enum Decoder {
+    /// Only numeric digits, as in part one.
+    Digits,
+    /// Numeric digits and spelled out numbers, as in part two.
+    Spelled,
+}
+
+impl Decoder {
+    pub fn decode(self, line: &str) -> Option<u32> {
+        match self {
+            Decoder::Digits => part1::calibration_value(line),
+            Decoder::Spelled => part2::calibration_value(line),
+        }
+    }
+}
+
+/// A decoded line of the calibration document, with the running total so far.
+#[derive(Debug, PartialEq)]
+pub struct Calibration {
+    pub line: usize,
+    pub value: u32,
+    pub total: u64,
+}
+
+#[derive(Debug)]
+pub enum CalibrationError {
+    Io(std::io::Error),
+    NoDigits { line: usize },
+}
+
+impl Display for CalibrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationError::Io(e) => write!(f, "Cannot read calibration document: {e}"),
+            CalibrationError::NoDigits { line } => {
+                write!(f, "Cannot find any number on line {line}")
+            }
+        }
+    }
+}
+
+/// Decodes a calibration document line by line, without holding more than a
+/// single line in memory.
+pub struct CalibrationStream<R> {
+    reader: R,
+    decoder: Decoder,
+    buffer: String,
+    line: usize,
+    total: u64,
+}
+
+impl<R: BufRead> CalibrationStream<R> {
+    pub fn new(reader: R, decoder: Decoder) -> Self {
+        CalibrationStream {
+            reader,
+            decoder,
+            buffer: String::new(),
+            line: 0,
+            total: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for CalibrationStream<R> {
+    type Item = Result<Calibration, CalibrationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.clear();
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => return Some(Err(CalibrationError::Io(e))),
+        }
+        self.line += 1;
+        let line = self.buffer.trim_end_matches(['\n', '\r']);
+        let Some(value) = self.decoder.decode(line) else {
+            return Some(Err(CalibrationError::NoDigits { line: self.line }));
+        };
+        self.total += value as u64;
+        Some(Ok(Calibration {
+            line: self.line,
+            value,
+            total: self.total,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, io::BufReader};
+
+    #[test]
+    fn test_stream_example() {
+        let file = File::open("input/day1/example2").expect("Need example file to test");
+        let last = CalibrationStream::new(BufReader::new(file), Decoder::Spelled)
+            .last()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            last,
+            Calibration {
+                line: 7,
+                value: 76,
+                total: 281
+            }
+        );
+    }
+
+    #[test]
+    fn test_stream_running_total() {
+        let input = "1abc2\r\npqr3stu8vwx\n";
+        let values: Vec<_> = CalibrationStream::new(input.as_bytes(), Decoder::Digits)
+            .map(|c| c.map(|c| (c.value, c.total)).unwrap())
+            .collect();
+        assert_eq!(values, [(12, 12), (38, 50)]);
+    }
+
+    #[test]
+    fn test_stream_line_without_digits() {
+        let input = "1abc2\nnothing\n";
+        let mut stream = CalibrationStream::new(input.as_bytes(), Decoder::Digits);
+        assert!(stream.next().unwrap().is_ok());
+        assert!(matches!(
+            stream.next(),
+            Some(Err(CalibrationError::NoDigits { line: 2 }))
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+use std::fs;
+
+use super::{
+    encoding::random_game,
+    inference::{BagModel, Sampling, SizePrior},
+    query::Query,
+    *,
+};
+use crate::{cli::Args, json::Value, rng::Rng};
+
+const USAGE: &str = "Usage: day2 <command> [--input FILE]
+Commands:
+  power [--palette red,green,blue]
+      Sum of the power of the smallest bag of every game. Without a palette
+      every colour found in the input is used.
+  filter [--config FILE] [--bag red=12,green=13,blue=14] [--min red=1]
+         [--max-draw N]
+      Sum of the ids of the games possible with the given bag. Options are
+      applied over the config file, without --config or --bag the bag of
+      part one is used.
+  report [--impossible] [filter options]
+      Lists every game as possible or impossible, with the reasons why.
+      --impossible  only list the impossible games
+  json [--decode]
+      Converts the games to a JSON array, or a JSON array back to games.
+  generate [--games N] [--seed S] [--palette red,green,blue] [--json]
+      Prints random games in the puzzle syntax.
+  infer [--sizes 1..=40] [--prior uniform|poisson:MEAN] [--replacement]
+        [--palette red,green,blue]
+      Most probable bag of every game given the draws and a prior over the
+      amount of cubes in the bag. Cubes of a draw are taken without
+      replacement unless --replacement is given.
+  query EXPRESSION [--palette red,green,blue]
+      Ids of the games matching the expression, with their count, sum and
+      total power. Example: \"max(blue) > 10 and draws > 4\".
+      Terms: id, draws, cubes, power, max(colour), min(colour), total(colour)
+      Operators: < <= > >= == !=, and, or, not, parentheses";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &["impossible", "decode", "json", "replacement"],
+        &[
+            "input", "palette", "config", "bag", "min", "max-draw", "games", "seed", "sizes",
+            "prior",
+        ],
+    )?;
+    match args.positional() {
+        [command] if command == "power" => power(&args),
+        [command] if command == "filter" => filter(&args),
+        [command] if command == "report" => report(&args),
+        [command] if command == "json" => json(&args),
+        [command] if command == "generate" => generate(&args),
+        [command] if command == "infer" => infer(&args),
+        [command, expression] if command == "query" => query(&args, expression),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn parse_games(input: &str, palette: Option<&Palette>) -> Result<Vec<Game>, String> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            match palette {
+                Some(palette) => palette.parse_game(line),
+                None => Game::try_from(line),
+            }
+            .map_err(|e| format!("Invalid game on line {}: {e}", i + 1))
+        })
+        .collect()
+}
+
+fn power(args: &Args) -> Result<(), String> {
+    let input = args.input(2)?;
+    let palette = args.value("palette").map(str::parse).transpose()?;
+    let games = parse_games(&input, palette.as_ref())?;
+    let palette = palette.unwrap_or_else(|| Palette::from_games(&games));
+
+    let power: usize = games
+        .iter()
+        .map(|g| g.lowest_possible_set(&palette).values().product::<usize>())
+        .sum();
+    println!("{power}");
+    Ok(())
+}
+
+fn build_filter(args: &Args) -> Result<Filter, String> {
+    let mut filter = match (args.value("config"), args.value("bag")) {
+        (Some(path), _) => fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {path}: {e}"))?
+            .parse()
+            .map_err(|e| format!("Invalid config {path}: {e}"))?,
+        (None, Some(_)) => Filter::default(),
+        (None, None) => Filter::puzzle(),
+    };
+    for directive in ["bag", "min", "max-draw"] {
+        if let Some(value) = args.value(directive) {
+            filter.apply(directive, value)?;
+        }
+    }
+    Ok(filter)
+}
+
+fn filter(args: &Args) -> Result<(), String> {
+    let filter = build_filter(args)?;
+    let games = parse_games(&args.input(2)?, None)?;
+
+    let sum: usize = games
+        .iter()
+        .filter(|g| filter.valid_game(g))
+        .map(|g| g.id)
+        .sum();
+    println!("{sum}");
+    Ok(())
+}
+
+fn report(args: &Args) -> Result<(), String> {
+    let filter = build_filter(args)?;
+    let games = parse_games(&args.input(2)?, None)?;
+
+    for game in games.iter() {
+        let verdict = filter.verdict(game);
+        if verdict.is_possible() {
+            if !args.flag("impossible") {
+                println!("Game {}: possible", game.id);
+            }
+        } else {
+            println!("Game {}: impossible", game.id);
+            for violation in verdict.violations.iter() {
+                println!("  {violation}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn json(args: &Args) -> Result<(), String> {
+    let input = args.input(2)?;
+    if args.flag("decode") {
+        let value: Value = input.parse().map_err(|e| format!("Invalid JSON: {e}"))?;
+        for (i, game) in value
+            .as_array()
+            .ok_or("Expected an array of games")?
+            .iter()
+            .enumerate()
+        {
+            let game = Game::try_from(game).map_err(|e| format!("Invalid game {i}: {e}"))?;
+            println!("{game}");
+        }
+    } else {
+        let games = parse_games(&input, None)?;
+        println!("{}", Value::Array(games.iter().map(Value::from).collect()));
+    }
+    Ok(())
+}
+
+fn generate(args: &Args) -> Result<(), String> {
+    let games = args.parsed("games")?.unwrap_or(100);
+    let mut rng = Rng::new(args.parsed("seed")?.unwrap_or(0));
+    let palette = match args.value("palette") {
+        Some(palette) => palette.parse()?,
+        None => Palette::default(),
+    };
+
+    let games: Vec<_> = (1..=games)
+        .map(|id| random_game(&mut rng, id, &palette))
+        .collect();
+    if args.flag("json") {
+        println!("{}", Value::Array(games.iter().map(Value::from).collect()));
+    } else {
+        games.iter().for_each(|g| println!("{g}"));
+    }
+    Ok(())
+}
+
+fn infer(args: &Args) -> Result<(), String> {
+    let sizes = args.range("sizes")?.unwrap_or(1..=40);
+    let prior: SizePrior = args.value("prior").unwrap_or("uniform").parse()?;
+    let sampling = if args.flag("replacement") {
+        Sampling::WithReplacement
+    } else {
+        Sampling::WithoutReplacement
+    };
+    let palette = args.value("palette").map(str::parse).transpose()?;
+    let games = parse_games(&args.input(2)?, palette.as_ref())?;
+    let palette = palette.unwrap_or_else(|| Palette::from_games(&games));
+
+    let model = BagModel::new(palette, sizes, prior, sampling)?;
+    for game in games.iter() {
+        match model.infer(game) {
+            Some(inference) => {
+                let bag: Vec<_> = inference
+                    .bag
+                    .iter()
+                    .map(|(colour, qty)| format!("{qty} {colour}"))
+                    .collect();
+                println!(
+                    "Game {}: {} (p={:.4} among {} bags)",
+                    game.id,
+                    bag.join(", "),
+                    inference.probability,
+                    inference.candidates
+                );
+            }
+            None => println!("Game {}: no bag can produce the draws", game.id),
+        }
+    }
+    Ok(())
+}
+
+fn query(args: &Args, expression: &str) -> Result<(), String> {
+    let query: Query = expression
+        .parse()
+        .map_err(|e| format!("Invalid query: {e}"))?;
+    let palette = args.value("palette").map(str::parse).transpose()?;
+    let games = parse_games(&args.input(2)?, palette.as_ref())?;
+    let palette = palette.unwrap_or_else(|| Palette::from_games(&games));
+
+    let matching: Vec<&Game> = games
+        .iter()
+        .filter(|g| query.matches(g, &palette))
+        .collect();
+    let ids: Vec<String> = matching.iter().map(|g| g.id.to_string()).collect();
+    let power: usize = matching
+        .iter()
+        .map(|g| g.lowest_possible_set(&palette).values().product::<usize>())
+        .sum();
+    println!("Games: {}", ids.join(", "));
+    println!("Count: {}", matching.len());
+    println!("Sum: {}", matching.iter().map(|g| g.id).sum::<usize>());
+    println!("Power: {power}");
+    Ok(())
+}
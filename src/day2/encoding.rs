@@ -0,0 +1,161 @@
+use super::{Colour, Draw, DrawInfo, Game, Palette};
+use crate::{json::Value, rng::Rng};
+
+impl From<&Game> for Value {
+    /// `{"id":1,"draws":[[{"qty":3,"colour":"blue"}]]}`
+    fn from(game: &Game) -> Self {
+        let draws = game
+            .draws
+            .iter()
+            .map(|draw_info| {
+                Value::Array(
+                    draw_info
+                        .sequence
+                        .iter()
+                        .map(|d| {
+                            Value::object([
+                                ("qty", Value::from(d.qty)),
+                                ("colour", Value::from(d.colour.0.as_str())),
+                            ])
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        Value::object([("id", Value::from(game.id)), ("draws", Value::Array(draws))])
+    }
+}
+
+impl TryFrom<&Value> for Draw {
+    type Error = String;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let qty = value
+            .get("qty")
+            .and_then(Value::as_usize)
+            .ok_or("Draw needs a numeric `qty`")?;
+        let colour = value
+            .get("colour")
+            .and_then(Value::as_str)
+            .ok_or("Draw needs a `colour` string")?;
+        let colour = Colour::try_from(colour).map_err(|_| format!("Invalid colour `{colour}`"))?;
+        Ok(Draw { qty, colour })
+    }
+}
+
+impl TryFrom<&Value> for Game {
+    type Error = String;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let id = value
+            .get("id")
+            .and_then(Value::as_usize)
+            .ok_or("Game needs a numeric `id`")?;
+        let draws = value
+            .get("draws")
+            .and_then(Value::as_array)
+            .ok_or("Game needs a `draws` array")?
+            .iter()
+            .map(|draw_info| {
+                let sequence: Vec<Draw> = draw_info
+                    .as_array()
+                    .ok_or("Every draw must be an array")?
+                    .iter()
+                    .map(Draw::try_from)
+                    .collect::<Result<_, _>>()?;
+                // Like the puzzle syntax, a draw shows at least one colour
+                if sequence.is_empty() {
+                    return Err("Every draw must show at least one colour".to_string());
+                }
+                Ok(DrawInfo { sequence })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Game { id, draws })
+    }
+}
+
+/// A game with one to five non empty draws, each colour of the palette
+/// shown at most once per draw like the puzzle games.
+pub(super) fn random_game(rng: &mut Rng, id: usize, palette: &Palette) -> Game {
+    let draws = (0..rng.range(1..6))
+        .map(|_| {
+            let mut colours: Vec<&Colour> = palette.colours.iter().collect();
+            let mut sequence = vec![];
+            for _ in 0..rng.range(1..colours.len() + 1) {
+                let colour = colours.swap_remove(rng.range(0..colours.len()));
+                sequence.push(Draw {
+                    qty: rng.range(1..21),
+                    colour: colour.clone(),
+                });
+            }
+            DrawInfo { sequence }
+        })
+        .collect();
+    Game { id, draws }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_palette(rng: &mut Rng) -> Palette {
+        let names = ["red", "green", "blue", "yellow", "magenta", "teal"];
+        let len = rng.range(1..names.len() + 1);
+        names[..len].join(",").parse().unwrap()
+    }
+
+    #[test]
+    fn test_display_is_puzzle_syntax() {
+        let line = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
+        let game = Game::try_from(line).unwrap();
+        assert_eq!(game.to_string(), line);
+    }
+
+    #[test]
+    fn test_json_format() {
+        let game = Game::try_from("Game 1: 3 blue, 4 red; 2 green").unwrap();
+        assert_eq!(
+            Value::from(&game).to_string(),
+            r#"{"id":1,"draws":[[{"qty":3,"colour":"blue"},{"qty":4,"colour":"red"}],[{"qty":2,"colour":"green"}]]}"#
+        );
+    }
+
+    #[test]
+    fn test_round_trip_random_games() {
+        let mut rng = Rng::new(2023);
+        for id in 0..500 {
+            let palette = random_palette(&mut rng);
+            let game = random_game(&mut rng, id, &palette);
+
+            let text = game.to_string();
+            assert_eq!(Game::try_from(text.as_str()).unwrap(), game, "{text}");
+
+            let json = Value::from(&game).to_string();
+            let value: Value = json.parse().unwrap();
+            assert_eq!(Game::try_from(&value).unwrap(), game, "{json}");
+        }
+    }
+
+    #[test]
+    fn test_empty_draws_are_rejected_both_ways() {
+        assert!(Game::try_from("Game 1: 1 red; ; 2 blue").is_err());
+        let json =
+            r#"{"id":1,"draws":[[{"qty":1,"colour":"red"}],[],[{"qty":2,"colour":"blue"}]]}"#;
+        let value: Value = json.parse().unwrap();
+        assert!(Game::try_from(&value).is_err());
+    }
+
+    #[test]
+    fn test_json_errors() {
+        for invalid in [
+            r#"{"draws":[]}"#,
+            r#"{"id":1,"draws":[{"qty":1,"colour":"red"}]}"#,
+            r#"{"id":1,"draws":[[{"qty":-1,"colour":"red"}]]}"#,
+            r#"{"id":1,"draws":[[{"qty":1,"colour":"Red"}]]}"#,
+            r#"{"id":1,"draws":[[]]}"#,
+        ] {
+            let value: Value = invalid.parse().unwrap();
+            assert!(Game::try_from(&value).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::{Colour, Game};
+
+/// Limits for a single colour, `min` is the fewest cubes of the colour some
+/// draw must show and `max` the most cubes any draw may show.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct FilterRule {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+/// Bag constraints a game must be consistent with.
+///
+/// Configuration uses one directive per line, `#` starts a comment:
+/// ```text
+/// bag red=12,green=13,blue=14
+/// min red=1
+/// max-draw 30
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct Filter {
+    pub colours_rules: Vec<(Colour, FilterRule)>,
+    /// Most cubes, of any colour, a single draw may show.
+    pub max_draw_total: Option<usize>,
+}
+
+impl Filter {
+    /// The bag of part one: 12 red, 13 green and 14 blue cubes.
+    pub fn puzzle() -> Self {
+        let mut filter = Filter::default();
+        filter
+            .apply("bag", "red=12,green=13,blue=14")
+            .expect("Valid bag");
+        filter
+    }
+
+    fn rule_mut(&mut self, colour: Colour) -> &mut FilterRule {
+        let i = match self.colours_rules.iter().position(|(c, _)| *c == colour) {
+            Some(i) => i,
+            None => {
+                self.colours_rules.push((colour, FilterRule::default()));
+                self.colours_rules.len() - 1
+            }
+        };
+        &mut self.colours_rules[i].1
+    }
+
+    /// Applies a single directive, overriding what was set before for the
+    /// same colour.
+    pub fn apply(&mut self, directive: &str, value: &str) -> Result<(), String> {
+        match directive {
+            "bag" | "min" => {
+                for entry in value.split(',').map(str::trim) {
+                    let (colour, qty) = entry
+                        .split_once('=')
+                        .ok_or(format!("Expected `colour=quantity`, found `{entry}`"))?;
+                    let colour = Colour::try_from(colour.trim())
+                        .map_err(|_| format!("Invalid colour `{colour}`"))?;
+                    let qty = qty
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Quantity of {colour} is not numeric"))?;
+                    let rule = self.rule_mut(colour);
+                    if directive == "bag" {
+                        rule.max = Some(qty);
+                    } else {
+                        rule.min = qty;
+                    }
+                }
+            }
+            "max-draw" => {
+                let max = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Draw limit `{value}` is not numeric"))?;
+                self.max_draw_total = Some(max);
+            }
+            _ => return Err(format!("Unknown directive `{directive}`")),
+        }
+        Ok(())
+    }
+
+    pub fn valid_game(&self, game: &Game) -> bool {
+        self.verdict(game).is_possible()
+    }
+
+    /// Checks the game against every rule, collecting all the violations
+    /// instead of stopping at the first one.
+    pub fn verdict(&self, game: &Game) -> Verdict {
+        let mut violations = vec![];
+        for (i, draw_seq) in game.draws.iter().enumerate() {
+            let draw = i + 1;
+            if let Some(max) = self.max_draw_total {
+                let total = draw_seq.sequence.iter().map(|d| d.qty).sum::<usize>();
+                if total > max {
+                    violations.push(Violation::DrawTotal { draw, total, max });
+                }
+            }
+            for d in draw_seq.sequence.iter() {
+                if let Some((_, rule)) = self
+                    .colours_rules
+                    .iter()
+                    .find(|(colour, _)| *colour == d.colour)
+                {
+                    match rule.max {
+                        Some(max) if d.qty > max => violations.push(Violation::AboveMax {
+                            draw,
+                            colour: d.colour.clone(),
+                            qty: d.qty,
+                            max,
+                        }),
+                        _ => {}
+                    }
+                } else {
+                    violations.push(Violation::UnknownColour {
+                        draw,
+                        colour: d.colour.clone(),
+                    });
+                }
+            }
+        }
+
+        let shown = |colour: &Colour| {
+            game.draws
+                .iter()
+                .flat_map(|di| di.sequence.iter())
+                .filter(|d| d.colour == *colour)
+                .map(|d| d.qty)
+                .max()
+        };
+        for (colour, rule) in self.colours_rules.iter() {
+            let most = shown(colour).unwrap_or_default();
+            if most < rule.min {
+                violations.push(Violation::BelowMin {
+                    colour: colour.clone(),
+                    shown: most,
+                    min: rule.min,
+                });
+            }
+        }
+
+        if !self.colours_rules.iter().any(|(c, _)| shown(c).is_some()) {
+            violations.push(Violation::NoRuledColour);
+        }
+
+        Verdict { violations }
+    }
+}
+
+/// Why a game is not possible with the bag of a [`Filter`], draws are
+/// numbered from 1.
+#[derive(Debug, PartialEq)]
+pub(super) enum Violation {
+    UnknownColour {
+        draw: usize,
+        colour: Colour,
+    },
+    AboveMax {
+        draw: usize,
+        colour: Colour,
+        qty: usize,
+        max: usize,
+    },
+    DrawTotal {
+        draw: usize,
+        total: usize,
+        max: usize,
+    },
+    BelowMin {
+        colour: Colour,
+        shown: usize,
+        min: usize,
+    },
+    NoRuledColour,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct Verdict {
+    pub violations: Vec<Violation>,
+}
+
+impl Verdict {
+    pub fn is_possible(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::UnknownColour { draw, colour } => {
+                write!(f, "draw {draw}: colour {colour} has no rule")
+            }
+            Violation::AboveMax {
+                draw,
+                colour,
+                qty,
+                max,
+            } => write!(
+                f,
+                "draw {draw}: {qty} {colour} is more than the {max} allowed"
+            ),
+            Violation::DrawTotal { draw, total, max } => {
+                write!(
+                    f,
+                    "draw {draw}: {total} cubes is more than the {max} allowed"
+                )
+            }
+            Violation::BelowMin { colour, shown, min } => {
+                write!(f, "at most {shown} {colour} shown, at least {min} required")
+            }
+            Violation::NoRuledColour => write!(f, "no draw shows a colour with a rule"),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = Filter::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (directive, value) = line
+                .split_once(char::is_whitespace)
+                .ok_or(format!("Line {}: expected `<directive> <value>`", i + 1))?;
+            filter
+                .apply(directive, value)
+                .map_err(|e| format!("Line {}: {e}", i + 1))?;
+        }
+        Ok(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(line: &str) -> Game {
+        line.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let filter: Filter = "# what if\nbag red=2, blue=3\nmin blue=1\n\nmax-draw 4"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            filter,
+            Filter {
+                colours_rules: vec![
+                    (
+                        Colour("red".to_string()),
+                        FilterRule {
+                            min: 0,
+                            max: Some(2)
+                        }
+                    ),
+                    (
+                        Colour("blue".to_string()),
+                        FilterRule {
+                            min: 1,
+                            max: Some(3)
+                        }
+                    ),
+                ],
+                max_draw_total: Some(4),
+            }
+        );
+        assert!("bag red".parse::<Filter>().is_err());
+        assert!("size 3".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_min_rule() {
+        let filter: Filter = "min red=3".parse().unwrap();
+        assert!(filter.valid_game(&game("Game 1: 1 red; 3 red")));
+        assert!(!filter.valid_game(&game("Game 1: 1 red; 2 red")));
+    }
+
+    #[test]
+    fn test_max_draw_total() {
+        let mut filter = Filter::puzzle();
+        filter.apply("max-draw", "5").unwrap();
+        assert!(filter.valid_game(&game("Game 1: 1 red, 4 blue; 5 green")));
+        assert!(!filter.valid_game(&game("Game 1: 2 red, 4 blue")));
+    }
+
+    #[test]
+    fn test_verdict_reasons() {
+        let mut filter = Filter::puzzle();
+        filter.apply("min", "green=2").unwrap();
+        let verdict = filter.verdict(&game("Game 1: 13 red, 1 green; 2 yellow"));
+        assert_eq!(
+            verdict.violations,
+            [
+                Violation::AboveMax {
+                    draw: 1,
+                    colour: Colour("red".to_string()),
+                    qty: 13,
+                    max: 12
+                },
+                Violation::UnknownColour {
+                    draw: 2,
+                    colour: Colour("yellow".to_string())
+                },
+                Violation::BelowMin {
+                    colour: Colour("green".to_string()),
+                    shown: 1,
+                    min: 2
+                },
+            ]
+        );
+        assert!(!verdict.is_possible());
+        assert!(filter.verdict(&game("Game 2: 2 green")).is_possible());
+    }
+}
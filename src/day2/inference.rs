@@ -0,0 +1,335 @@
+use std::{ops::RangeInclusive, str::FromStr};
+
+use super::{Colour, Game, Palette};
+
+/// How the cubes of a single draw are taken out of the bag. Between draws
+/// the cubes always go back into the bag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Sampling {
+    WithReplacement,
+    WithoutReplacement,
+}
+
+/// Prior weight of each total amount of cubes in the bag, every split of
+/// that total between the colours is equally likely.
+#[derive(Debug, PartialEq)]
+pub(super) enum SizePrior {
+    Uniform,
+    Poisson(f64),
+}
+
+impl FromStr for SizePrior {
+    type Err = String;
+
+    /// `uniform` or `poisson:<mean>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "uniform" => Ok(SizePrior::Uniform),
+            Some(("poisson", mean)) => match mean.parse::<f64>() {
+                Ok(mean) if mean > 0.0 => Ok(SizePrior::Poisson(mean)),
+                _ => Err(format!("Poisson mean `{mean}` must be a positive number")),
+            },
+            _ => Err(format!(
+                "Unknown prior `{s}`, expected uniform or poisson:<mean>"
+            )),
+        }
+    }
+}
+
+/// The most probable bag of a game.
+#[derive(Debug, PartialEq)]
+pub(super) struct Inference {
+    pub bag: Vec<(Colour, usize)>,
+    /// Posterior probability of the bag among every candidate.
+    pub probability: f64,
+    /// Candidate bags that can explain the draws.
+    pub candidates: usize,
+}
+
+/// Largest bag the model accepts.
+const MAX_BAG_SIZE: usize = 1000;
+/// Most candidate bags a game may be checked against, roughly ten seconds
+/// of enumeration.
+const MAX_CANDIDATES: u128 = 100_000_000;
+
+/// Ways of splitting `total` cubes between `colours` colours,
+/// `C(total + colours - 1, colours - 1)`, `None` past `u128`.
+fn compositions_count(total: usize, colours: usize) -> Option<u128> {
+    let mut count: u128 = 1;
+    for i in 1..colours as u128 {
+        count = count.checked_mul(total as u128 + i)? / i;
+    }
+    Some(count)
+}
+
+pub(super) struct BagModel {
+    palette: Palette,
+    sizes: RangeInclusive<usize>,
+    prior: SizePrior,
+    sampling: Sampling,
+    ln_factorial: Vec<f64>,
+}
+
+impl BagModel {
+    pub fn new(
+        palette: Palette,
+        sizes: RangeInclusive<usize>,
+        prior: SizePrior,
+        sampling: Sampling,
+    ) -> Result<Self, String> {
+        if *sizes.end() > MAX_BAG_SIZE {
+            return Err(format!(
+                "Bags of {} cubes are too large, the limit is {MAX_BAG_SIZE}",
+                sizes.end()
+            ));
+        }
+        let colours = palette.colours.len();
+        let candidates = sizes
+            .clone()
+            .filter(|&t| t > 0 && colours > 0)
+            .try_fold(0u128, |sum, total| {
+                sum.checked_add(compositions_count(total, colours)?)
+            });
+        if candidates.is_none_or(|c| c > MAX_CANDIDATES) {
+            return Err(format!(
+                "Too many candidate bags for {colours} colours and sizes {}..={}, the limit is {MAX_CANDIDATES}",
+                sizes.start(),
+                sizes.end()
+            ));
+        }
+        // Big enough for the compositions count of the largest bag
+        let len = sizes.end() + colours + 1;
+        let ln_factorial = (0..len)
+            .scan(0f64, |acc, n| {
+                if n > 0 {
+                    *acc += (n as f64).ln();
+                }
+                Some(*acc)
+            })
+            .collect();
+        Ok(BagModel {
+            palette,
+            sizes,
+            prior,
+            sampling,
+            ln_factorial,
+        })
+    }
+
+    fn ln_factorial(&self, n: usize) -> f64 {
+        match self.ln_factorial.get(n) {
+            Some(&value) => value,
+            None => (1..=n).map(|i| (i as f64).ln()).sum(),
+        }
+    }
+
+    fn ln_choose(&self, n: usize, k: usize) -> f64 {
+        self.ln_factorial(n) - self.ln_factorial(k) - self.ln_factorial(n - k)
+    }
+
+    /// Log prior of one specific bag holding `total` cubes.
+    fn ln_prior(&self, total: usize) -> f64 {
+        let colours = self.palette.colours.len();
+        let compositions = self.ln_choose(total + colours - 1, colours - 1);
+        let size = match self.prior {
+            SizePrior::Uniform => 0.0,
+            SizePrior::Poisson(mean) => total as f64 * mean.ln() - self.ln_factorial(total),
+        };
+        size - compositions
+    }
+
+    /// Log likelihood of the game draws for the bag, `None` when the bag
+    /// cannot produce them.
+    fn ln_likelihood(&self, draws: &[Vec<usize>], bag: &[usize]) -> Option<f64> {
+        let total: usize = bag.iter().sum();
+        let mut ln_likelihood = 0.0;
+        for draw in draws {
+            let taken: usize = draw.iter().sum();
+            match self.sampling {
+                Sampling::WithoutReplacement => {
+                    if taken > total || draw.iter().zip(bag).any(|(x, n)| x > n) {
+                        return None;
+                    }
+                    ln_likelihood += draw
+                        .iter()
+                        .zip(bag)
+                        .map(|(&x, &n)| self.ln_choose(n, x))
+                        .sum::<f64>()
+                        - self.ln_choose(total, taken);
+                }
+                Sampling::WithReplacement => {
+                    if draw.iter().zip(bag).any(|(&x, &n)| x > 0 && n == 0) {
+                        return None;
+                    }
+                    ln_likelihood += self.ln_factorial(taken);
+                    for (&x, &n) in draw.iter().zip(bag).filter(|(&x, _)| x > 0) {
+                        ln_likelihood +=
+                            x as f64 * (n as f64 / total as f64).ln() - self.ln_factorial(x);
+                    }
+                }
+            }
+        }
+        Some(ln_likelihood)
+    }
+
+    /// Every way of splitting `total` cubes between the colours.
+    fn compositions(
+        total: usize,
+        colours: usize,
+        bag: &mut Vec<usize>,
+        f: &mut impl FnMut(&[usize]),
+    ) {
+        if colours == 1 {
+            bag.push(total);
+            f(bag);
+            bag.pop();
+            return;
+        }
+        for n in 0..=total {
+            bag.push(n);
+            Self::compositions(total - n, colours - 1, bag, f);
+            bag.pop();
+        }
+    }
+
+    /// Most probable bag for the game, `None` if no bag in the model can
+    /// explain the draws.
+    pub fn infer(&self, game: &Game) -> Option<Inference> {
+        let colours = &self.palette.colours;
+        if colours.is_empty() || game.colours().any(|c| !self.palette.contains(c)) {
+            return None;
+        }
+        let draws: Vec<Vec<usize>> = game
+            .draws
+            .iter()
+            .map(|draw_info| {
+                colours
+                    .iter()
+                    .map(|c| {
+                        draw_info
+                            .sequence
+                            .iter()
+                            .filter(|d| d.colour == *c)
+                            .map(|d| d.qty)
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Streaming log-sum-exp: `evidence` is the sum of the weights
+        // scaled by the best one, rescaled whenever a better bag shows up
+        let mut best: Option<(Vec<usize>, f64)> = None;
+        let mut evidence = 0f64;
+        let mut candidates = 0;
+        for total in self.sizes.clone().filter(|&t| t > 0) {
+            let ln_prior = self.ln_prior(total);
+            Self::compositions(total, colours.len(), &mut vec![], &mut |bag| {
+                let Some(ln_likelihood) = self.ln_likelihood(&draws, bag) else {
+                    return;
+                };
+                let weight = ln_prior + ln_likelihood;
+                candidates += 1;
+                match &mut best {
+                    // Ties go to the later bag
+                    Some((best_bag, best_weight)) if weight >= *best_weight => {
+                        evidence = evidence * (*best_weight - weight).exp() + 1.0;
+                        best_bag.clear();
+                        best_bag.extend_from_slice(bag);
+                        *best_weight = weight;
+                    }
+                    Some((_, best_weight)) => evidence += (weight - *best_weight).exp(),
+                    None => {
+                        evidence = 1.0;
+                        best = Some((bag.to_vec(), weight));
+                    }
+                }
+            });
+        }
+
+        let (best, _) = best?;
+        Some(Inference {
+            bag: colours.iter().cloned().zip(best).collect(),
+            probability: 1.0 / evidence,
+            candidates,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(palette: &str, sizes: RangeInclusive<usize>, sampling: Sampling) -> BagModel {
+        BagModel::new(
+            palette.parse().unwrap(),
+            sizes,
+            SizePrior::Uniform,
+            sampling,
+        )
+        .unwrap()
+    }
+
+    fn bag(inference: &Inference) -> Vec<usize> {
+        inference.bag.iter().map(|(_, n)| *n).collect()
+    }
+
+    #[test]
+    fn test_parse_prior() {
+        assert_eq!("uniform".parse(), Ok(SizePrior::Uniform));
+        assert_eq!("poisson:39".parse(), Ok(SizePrior::Poisson(39.0)));
+        assert!("poisson:-1".parse::<SizePrior>().is_err());
+        assert!("normal".parse::<SizePrior>().is_err());
+    }
+
+    #[test]
+    fn test_without_replacement_needs_enough_cubes() {
+        let game = Game::try_from("Game 1: 2 red").unwrap();
+        let inference = model("red,blue", 2..=2, Sampling::WithoutReplacement)
+            .infer(&game)
+            .unwrap();
+        assert_eq!(bag(&inference), [2, 0]);
+        assert_eq!(inference.probability, 1.0);
+        assert_eq!(inference.candidates, 1);
+    }
+
+    #[test]
+    fn test_with_replacement_posterior() {
+        // Bags (1,0), (2,0) and (1,1) explain the draw, with priors 1/4,
+        // 1/6, 1/6 and likelihoods 1, 1, 1/8
+        let game = Game::try_from("Game 1: 3 red").unwrap();
+        let inference = model("red,blue", 1..=2, Sampling::WithReplacement)
+            .infer(&game)
+            .unwrap();
+        assert_eq!(bag(&inference), [1, 0]);
+        assert!((inference.probability - 12.0 / 21.0).abs() < 1e-9);
+        assert_eq!(inference.candidates, 3);
+    }
+
+    #[test]
+    fn test_model_bounds() {
+        assert_eq!(compositions_count(3, 2), Some(4));
+        assert_eq!(compositions_count(40, 6), Some(1_221_759));
+        let new = |palette: &str, sizes| {
+            BagModel::new(
+                palette.parse().unwrap(),
+                sizes,
+                SizePrior::Uniform,
+                Sampling::WithReplacement,
+            )
+        };
+        assert!(new("red,green", 1..=usize::MAX).is_err());
+        assert!(new("red,green", 1..=MAX_BAG_SIZE).is_ok());
+        assert!(new("red,green,blue,teal,pink,gold", 1..=40).is_ok());
+        assert!(new("red,green,blue,teal,pink,gold,gray,white", 1..=100).is_err());
+    }
+
+    #[test]
+    fn test_no_possible_bag() {
+        let game = Game::try_from("Game 1: 5 red; 1 green").unwrap();
+        let without = model("red,green", 1..=4, Sampling::WithoutReplacement);
+        assert_eq!(without.infer(&game), None);
+        let unknown = model("red", 1..=10, Sampling::WithReplacement);
+        assert_eq!(unknown.infer(&game), None);
+    }
+}
@@ -1,8 +1,21 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+pub(crate) mod cli;
+mod encoding;
+mod filter;
+mod inference;
+mod query;
+
+use filter::Filter;
+
+/// Name of a cube colour, any lowercase word is a valid colour.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Colour(String);
+
+/// The colours a bag is allowed to contain.
 #[derive(Debug, PartialEq)]
-enum Colour {
-    Red,
-    Green,
-    Blue,
+struct Palette {
+    colours: Vec<Colour>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -28,11 +41,13 @@ enum ParseError {
     InvalidKeyFormat,
     GameIdIsNotNumeric,
     InvalidDraw(DrawInfoParseError),
+    UnknownColour(Colour),
 }
 
 #[derive(Debug)]
 enum DrawInfoParseError {
     InvalidDraw(DrawParseError),
+    Empty,
 }
 
 #[derive(Debug)]
@@ -44,6 +59,37 @@ enum DrawParseError {
 
 struct InvalidColour;
 
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidFormat => write!(f, "expected `Game <id>: <draws>`"),
+            ParseError::InvalidKeyFormat => write!(f, "expected `Game <id>` before the colon"),
+            ParseError::GameIdIsNotNumeric => write!(f, "game id is not numeric"),
+            ParseError::InvalidDraw(e) => write!(f, "{e}"),
+            ParseError::UnknownColour(c) => write!(f, "colour {c} is not in the palette"),
+        }
+    }
+}
+
+impl Display for DrawInfoParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DrawInfoParseError::InvalidDraw(e) => write!(f, "invalid draw, {e}"),
+            DrawInfoParseError::Empty => write!(f, "empty draw, expected at least one colour"),
+        }
+    }
+}
+
+impl Display for DrawParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DrawParseError::InvalidFormat => write!(f, "expected `<quantity> <colour>`"),
+            DrawParseError::QuantityIsNotNumeric => write!(f, "quantity is not numeric"),
+            DrawParseError::InvalidColour => write!(f, "colour must be a lowercase word"),
+        }
+    }
+}
+
 impl TryFrom<&str> for Game {
     type Error = ParseError;
 
@@ -71,7 +117,10 @@ impl TryFrom<&str> for DrawInfo {
             .split_terminator(", ")
             .map(|draw| draw.try_into())
             .collect();
-        let sequence = sequence.map_err(DrawInfoParseError::InvalidDraw)?;
+        let sequence: Vec<Draw> = sequence.map_err(DrawInfoParseError::InvalidDraw)?;
+        if sequence.is_empty() {
+            return Err(DrawInfoParseError::Empty);
+        }
         Ok(DrawInfo { sequence })
     }
 }
@@ -91,62 +140,125 @@ impl TryFrom<&str> for Draw {
     }
 }
 
+impl Display for Game {
+    /// Formats the game back into the puzzle syntax.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        for (i, draw) in self.draws.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{draw}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DrawInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, draw) in self.sequence.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{draw}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Draw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.qty, self.colour)
+    }
+}
+
 impl TryFrom<&str> for Colour {
     type Error = InvalidColour;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        use Colour::*;
-        match value {
-            "red" => Ok(Red),
-            "blue" => Ok(Blue),
-            "green" => Ok(Green),
-            _ => Err(InvalidColour),
+        if !value.is_empty() && value.bytes().all(|c| c.is_ascii_lowercase()) {
+            Ok(Colour(value.to_string()))
+        } else {
+            Err(InvalidColour)
         }
     }
 }
 
-struct FilterRule {
-    max: usize,
+impl Display for Colour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
-struct Filter {
-    colours_rules: Vec<(Colour, FilterRule)>,
+impl Default for Palette {
+    /// The colours of the puzzle: red, green and blue.
+    fn default() -> Self {
+        "red,green,blue".parse().expect("Valid palette")
+    }
 }
 
-impl Filter {
-    fn valid_game(&self, game: &Game) -> bool {
-        for draw_seq in game.draws.iter() {
-            for draw in draw_seq.sequence.iter() {
-                if let Some((_, rule)) = self
-                    .colours_rules
-                    .iter()
-                    .find(|(colour, _)| *colour == draw.colour)
-                {
-                    if draw.qty > rule.max {
-                        return false;
-                    }
-                } else {
-                    return false;
-                }
+impl FromStr for Palette {
+    type Err = String;
+
+    /// Parses a comma separated list of colours, like `red,green,blue`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut colours: Vec<Colour> = vec![];
+        for name in s.split(',').map(str::trim) {
+            let colour = Colour::try_from(name)
+                .map_err(|_| format!("Invalid colour `{name}` in palette"))?;
+            if colours.contains(&colour) {
+                return Err(format!("Colour {colour} appears twice in palette"));
             }
+            colours.push(colour);
         }
+        Ok(Palette { colours })
+    }
+}
 
-        self.colours_rules.iter().any(|(c, _)| {
-            game.draws
-                .iter()
-                .any(|di| di.sequence.iter().any(|d| d.colour == *c))
-        })
+impl Palette {
+    /// Every colour that appears in the games, in order of first appearance.
+    fn from_games(games: &[Game]) -> Self {
+        let mut colours: Vec<Colour> = vec![];
+        for colour in games.iter().flat_map(Game::colours) {
+            if !colours.contains(colour) {
+                colours.push(colour.clone());
+            }
+        }
+        Palette { colours }
+    }
+
+    fn contains(&self, colour: &Colour) -> bool {
+        self.colours.contains(colour)
+    }
+
+    /// Parses a game, rejecting colours outside of the palette.
+    fn parse_game(&self, value: &str) -> Result<Game, ParseError> {
+        let game = Game::try_from(value)?;
+        if let Some(colour) = game.colours().find(|c| !self.contains(c)) {
+            return Err(ParseError::UnknownColour(colour.clone()));
+        }
+        Ok(game)
     }
 }
 
 impl Game {
-    fn lowest_possible_set(self) -> [usize; 3] {
+    fn colours(&self) -> impl Iterator<Item = &Colour> {
+        self.draws
+            .iter()
+            .flat_map(|d| d.sequence.iter())
+            .map(|d| &d.colour)
+    }
+
+    /// Fewest cubes of each colour the bag could hold, every colour of the
+    /// palette is present even if the game never shows it.
+    fn lowest_possible_set(&self, palette: &Palette) -> BTreeMap<Colour, usize> {
+        let set = palette.colours.iter().map(|c| (c.clone(), 0)).collect();
         self.draws
-            .into_iter()
-            .flat_map(|d| d.sequence)
-            .fold([0usize; 3], |mut acc, d| {
-                let i = d.colour as usize;
-                acc[i] = acc[i].max(d.qty);
+            .iter()
+            .flat_map(|d| d.sequence.iter())
+            .fold(set, |mut acc, d| {
+                let qty = acc.entry(d.colour.clone()).or_default();
+                *qty = (*qty).max(d.qty);
                 acc
             })
     }
@@ -159,15 +271,9 @@ pub(crate) mod part1 {
         let games: Vec<Game> = input
             .lines()
             .map(|l| l.try_into())
-            .collect::<Result<_, _>>()
-            .expect("Invalid input");
-        let filter = Filter {
-            colours_rules: vec![
-                (Colour::Red, FilterRule { max: 12 }),
-                (Colour::Green, FilterRule { max: 13 }),
-                (Colour::Blue, FilterRule { max: 14 }),
-            ],
-        };
+            .collect::<Result<_, ParseError>>()
+            .unwrap_or_else(|e| panic!("Invalid input: {e}"));
+        let filter = Filter::puzzle();
 
         games
             .iter()
@@ -199,12 +305,13 @@ pub(crate) mod part2 {
         let games: Vec<Game> = input
             .lines()
             .map(|l| l.try_into())
-            .collect::<Result<_, _>>()
-            .expect("Invalid input");
+            .collect::<Result<_, ParseError>>()
+            .unwrap_or_else(|e| panic!("Invalid input: {e}"));
+        let palette = Palette::default();
         games
-            .into_iter()
-            .map(Game::lowest_possible_set)
-            .map(|d| d.iter().product::<usize>())
+            .iter()
+            .map(|g| g.lowest_possible_set(&palette))
+            .map(|set| set.values().product::<usize>())
             .sum::<usize>()
             .to_string()
     }
@@ -228,6 +335,10 @@ pub(crate) mod part2 {
 mod tests {
     use super::*;
 
+    fn colour(name: &str) -> Colour {
+        Colour(name.to_string())
+    }
+
     #[test]
     fn test_parse_draw() {
         let draw: Draw = "1 red".try_into().unwrap();
@@ -235,7 +346,7 @@ mod tests {
             draw,
             Draw {
                 qty: 1,
-                colour: Colour::Red
+                colour: colour("red")
             }
         );
     }
@@ -249,11 +360,11 @@ mod tests {
                 sequence: vec![
                     Draw {
                         qty: 1,
-                        colour: Colour::Red
+                        colour: colour("red")
                     },
                     Draw {
                         qty: 2,
-                        colour: Colour::Blue
+                        colour: colour("blue")
                     }
                 ]
             }
@@ -271,15 +382,47 @@ mod tests {
                     sequence: vec!(
                         Draw {
                             qty: 1,
-                            colour: Colour::Red
+                            colour: colour("red")
                         },
                         Draw {
                             qty: 2,
-                            colour: Colour::Blue
+                            colour: colour("blue")
                         }
                     )
                 })
             }
         );
     }
+
+    #[test]
+    fn test_parse_custom_colour() {
+        let draw: Draw = "3 yellow".try_into().unwrap();
+        assert_eq!(draw.colour, colour("yellow"));
+        assert!(Draw::try_from("3 Yellow!").is_err());
+    }
+
+    #[test]
+    fn test_palette_rejects_unknown_colour() {
+        let palette: Palette = "red,yellow".parse().unwrap();
+        assert!(palette.parse_game("Game 1: 1 red, 2 yellow").is_ok());
+        assert!(matches!(
+            palette.parse_game("Game 1: 1 red, 2 blue"),
+            Err(ParseError::UnknownColour(c)) if c == colour("blue")
+        ));
+        assert!("red,red".parse::<Palette>().is_err());
+    }
+
+    #[test]
+    fn test_lowest_possible_set_with_palette() {
+        let game: Game = "Game 1: 1 red, 2 yellow; 4 yellow".try_into().unwrap();
+        let palette = Palette::from_games(&[game]);
+        assert_eq!(palette.colours, [colour("red"), colour("yellow")]);
+
+        let game: Game = "Game 2: 3 yellow".try_into().unwrap();
+        let set = game.lowest_possible_set(&palette);
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            [(colour("red"), 0), (colour("yellow"), 3)]
+        );
+    }
 }
@@ -0,0 +1,335 @@
+use std::str::FromStr;
+
+use super::{Colour, Game, Palette};
+
+/// A number computed from a game.
+#[derive(Debug, PartialEq)]
+pub(super) enum Term {
+    Number(usize),
+    Id,
+    /// Amount of draws in the game.
+    Draws,
+    /// Cubes shown over all the draws.
+    Cubes,
+    /// Power of the smallest bag of the game.
+    Power,
+    /// Most cubes of the colour shown in a single draw.
+    Max(Colour),
+    /// Fewest cubes of the colour shown in a single draw, zero when some
+    /// draw does not show the colour.
+    Min(Colour),
+    /// Cubes of the colour shown over all the draws.
+    Total(Colour),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(super) enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+/// Filter over games, like `max(blue) > 10 and not draws <= 4`.
+///
+/// Comparisons between terms can be combined with `and`, `or`, `not` and
+/// parentheses, the terms are numbers, `id`, `draws`, `cubes`, `power`,
+/// `max(colour)`, `min(colour)` and `total(colour)`.
+#[derive(Debug, PartialEq)]
+pub(super) enum Query {
+    Compare(Term, Comparison, Term),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+/// Cubes of the colour shown by each draw of the game.
+fn shown<'a>(game: &'a Game, colour: &'a Colour) -> impl Iterator<Item = usize> + 'a {
+    game.draws.iter().map(move |draw_info| {
+        draw_info
+            .sequence
+            .iter()
+            .filter(|d| d.colour == *colour)
+            .map(|d| d.qty)
+            .sum::<usize>()
+    })
+}
+
+impl Term {
+    fn eval(&self, game: &Game, palette: &Palette) -> usize {
+        match self {
+            Term::Number(n) => *n,
+            Term::Id => game.id,
+            Term::Draws => game.draws.len(),
+            Term::Cubes => game
+                .draws
+                .iter()
+                .flat_map(|d| d.sequence.iter())
+                .map(|d| d.qty)
+                .sum(),
+            Term::Power => game.lowest_possible_set(palette).values().product(),
+            Term::Max(colour) => shown(game, colour).max().unwrap_or_default(),
+            Term::Min(colour) => shown(game, colour).min().unwrap_or_default(),
+            Term::Total(colour) => shown(game, colour).sum(),
+        }
+    }
+}
+
+impl Query {
+    pub fn matches(&self, game: &Game, palette: &Palette) -> bool {
+        match self {
+            Query::Compare(left, comparison, right) => {
+                let (left, right) = (left.eval(game, palette), right.eval(game, palette));
+                match comparison {
+                    Comparison::Less => left < right,
+                    Comparison::LessEqual => left <= right,
+                    Comparison::Greater => left > right,
+                    Comparison::GreaterEqual => left >= right,
+                    Comparison::Equal => left == right,
+                    Comparison::NotEqual => left != right,
+                }
+            }
+            Query::Not(query) => !query.matches(game, palette),
+            Query::And(left, right) => left.matches(game, palette) && right.matches(game, palette),
+            Query::Or(left, right) => left.matches(game, palette) || right.matches(game, palette),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Word(String),
+    Number(usize),
+    Comparison(Comparison),
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let mut take_while = |first: usize, accept: fn(char) -> bool| {
+            let mut end = first + 1;
+            while let Some(&(j, c)) = chars.peek() {
+                if !accept(c) {
+                    break;
+                }
+                end = j + c.len_utf8();
+                chars.next();
+            }
+            &s[first..end]
+        };
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_ascii_digit() => {
+                let number = take_while(i, |c| c.is_ascii_digit());
+                Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| format!("Number {number} is too big"))?,
+                )
+            }
+            c if c.is_ascii_lowercase() => {
+                Token::Word(take_while(i, |c| c.is_ascii_lowercase() || c == '_').to_string())
+            }
+            '<' | '>' | '=' | '!' => {
+                let op = take_while(i, |c| c == '=');
+                Token::Comparison(match op {
+                    "<" => Comparison::Less,
+                    "<=" => Comparison::LessEqual,
+                    ">" => Comparison::Greater,
+                    ">=" => Comparison::GreaterEqual,
+                    "=" | "==" => Comparison::Equal,
+                    "!=" => Comparison::NotEqual,
+                    _ => return Err(format!("Unknown operator `{op}` at {i}")),
+                })
+            }
+            c => return Err(format!("Unexpected `{c}` at {i}")),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.tokens.get(self.position), Some(Token::Word(w)) if w == word) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Query, String> {
+        let mut query = self.and()?;
+        while self.eat_word("or") {
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, String> {
+        let mut query = self.not()?;
+        while self.eat_word("and") {
+            query = Query::And(Box::new(query), Box::new(self.not()?));
+        }
+        Ok(query)
+    }
+
+    fn not(&mut self) -> Result<Query, String> {
+        if self.eat_word("not") {
+            return Ok(Query::Not(Box::new(self.not()?)));
+        }
+        if self.tokens.get(self.position) == Some(&Token::Open) {
+            self.position += 1;
+            let query = self.or()?;
+            return match self.next() {
+                Some(Token::Close) => Ok(query),
+                _ => Err("Expected `)`".to_string()),
+            };
+        }
+        let left = self.term()?;
+        let Some(Token::Comparison(comparison)) = self.next() else {
+            return Err("Expected a comparison like `>` or `==`".to_string());
+        };
+        Ok(Query::Compare(left, comparison, self.term()?))
+    }
+
+    fn term(&mut self) -> Result<Term, String> {
+        let word = match self.next() {
+            Some(Token::Number(n)) => return Ok(Term::Number(n)),
+            Some(Token::Word(word)) => word,
+            Some(token) => return Err(format!("Expected a term, found {token:?}")),
+            None => return Err("Expected a term, found the end".to_string()),
+        };
+        let term = match word.as_str() {
+            "id" => Term::Id,
+            "draws" => Term::Draws,
+            "cubes" => Term::Cubes,
+            "power" => Term::Power,
+            "max" | "min" | "total" => {
+                let colour = match (self.next(), self.next(), self.next()) {
+                    (Some(Token::Open), Some(Token::Word(colour)), Some(Token::Close)) => {
+                        Colour::try_from(colour.as_str())
+                            .map_err(|_| format!("Invalid colour `{colour}`"))?
+                    }
+                    _ => return Err(format!("Expected `{word}(colour)`")),
+                };
+                match word.as_str() {
+                    "max" => Term::Max(colour),
+                    "min" => Term::Min(colour),
+                    _ => Term::Total(colour),
+                }
+            }
+            _ => return Err(format!("Unknown term `{word}`")),
+        };
+        Ok(term)
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let query = parser.or()?;
+        if parser.position < parser.tokens.len() {
+            return Err(format!(
+                "Unexpected {:?} after the query",
+                parser.tokens[parser.position]
+            ));
+        }
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn matching_ids(query: &str) -> Vec<usize> {
+        let input = fs::read_to_string("input/day2/example").expect("Need example file to test");
+        let query: Query = query.parse().unwrap();
+        let palette = Palette::default();
+        input
+            .lines()
+            .map(|l| Game::try_from(l).unwrap())
+            .filter(|g| query.matches(g, &palette))
+            .map(|g| g.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let query: Query = "not id = 1 or id < 3 and draws >= 2".parse().unwrap();
+        assert_eq!(
+            query,
+            Query::Or(
+                Box::new(Query::Not(Box::new(Query::Compare(
+                    Term::Id,
+                    Comparison::Equal,
+                    Term::Number(1)
+                )))),
+                Box::new(Query::And(
+                    Box::new(Query::Compare(Term::Id, Comparison::Less, Term::Number(3))),
+                    Box::new(Query::Compare(
+                        Term::Draws,
+                        Comparison::GreaterEqual,
+                        Term::Number(2)
+                    ))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_queries_on_example() {
+        assert_eq!(matching_ids("max(blue) > 10"), [4]);
+        assert_eq!(matching_ids("draws > 2"), [1, 2, 3, 4]);
+        assert_eq!(matching_ids("power >= 1560"), [3]);
+        assert_eq!(
+            matching_ids("(id = 1 or id = 5) and total(red) > 4"),
+            [1, 5]
+        );
+        assert_eq!(matching_ids("min(green) != 0"), [2, 3, 4, 5]);
+        assert_eq!(matching_ids("cubes == 18"), [1]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for invalid in [
+            "",
+            "id",
+            "id >",
+            "max(blue > 1",
+            "size > 1",
+            "id => 1",
+            "(id > 1",
+            "id > 1 id",
+            "id > 1 $",
+        ] {
+            assert!(invalid.parse::<Query>().is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use super::{
+    gear::{AdjacentCount, GearRule},
+    graph::{to_dot, to_json},
+    render::{legend, render, Style, View},
+    validate::{normalize, validate, Normalization},
+    *,
+};
+use crate::cli::Args;
+
+const USAGE: &str = "Usage: day3 <command> [--input FILE] [normalization]
+Every command refuses schematics with ragged rows, non-ASCII characters or
+CRLF line endings, unless normalized with:
+  --strip-cr                  remove the carriage return ending the rows
+  --pad                       fill the narrower rows with `.`
+  --replace-non-ascii CHAR    replace every non-ASCII character
+Commands:
+  check
+      Lists the issues left in the schematic after normalization.
+  solve
+      Answers both parts of the puzzle.
+  gears [--symbols '*'] [--exactly N | --at-least N]
+        [--combine product|sum|max] [--list]
+      Sum of the ratios of the gears matching the rule, by default the rule
+      of part two. --list prints every gear with its position.
+  render [--plain] [--rows A..B] [--cols A..B] [gear options]
+      Prints the schematic with part numbers, other numbers, symbols and
+      gears in distinct colours, or with markers under each row when
+      --plain is given, optionally restricted to a window.
+  graph [--format dot|json]
+      Exports the adjacency between symbols and numbers as a bipartite
+      graph, in Graphviz DOT by default.";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &["list", "plain", "strip-cr", "pad"],
+        &[
+            "input",
+            "symbols",
+            "exactly",
+            "at-least",
+            "combine",
+            "rows",
+            "cols",
+            "format",
+            "replace-non-ascii",
+        ],
+    )?;
+    match args.positional() {
+        [command] if command == "check" => check(&args),
+        [command] if command == "solve" => solve(&args),
+        [command] if command == "gears" => gears(&args),
+        [command] if command == "render" => show(&args),
+        [command] if command == "graph" => graph(&args),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn normalized_input(args: &Args) -> Result<String, String> {
+    let normalization = Normalization {
+        strip_cr: args.flag("strip-cr"),
+        pad: args.flag("pad"),
+        replace_non_ascii: args.parsed("replace-non-ascii")?,
+    };
+    Ok(normalize(&args.input(3)?, &normalization))
+}
+
+/// Parses the normalized input, failing if any issue is left.
+fn load_schematic(args: &Args) -> Result<EngineSchematic, String> {
+    let input = normalized_input(args)?;
+    let issues = validate(&input);
+    if !issues.is_empty() {
+        let issues: Vec<String> = issues.iter().map(|i| format!("  {i}")).collect();
+        return Err(format!(
+            "Invalid schematic, see the normalization options:\n{}",
+            issues.join("\n")
+        ));
+    }
+    input.parse()
+}
+
+fn check(args: &Args) -> Result<(), String> {
+    let issues = validate(&normalized_input(args)?);
+    if issues.is_empty() {
+        println!("No issues");
+    }
+    for issue in issues {
+        println!("{issue}");
+    }
+    Ok(())
+}
+
+fn solve(args: &Args) -> Result<(), String> {
+    let schematic = load_schematic(args)?;
+    let parts: usize = schematic.part_numbers().map(|n| n.value).sum();
+    println!("Part one: {parts}");
+    println!("Part two: {}", GearRule::default().total(&schematic));
+    Ok(())
+}
+
+fn gear_rule(args: &Args) -> Result<GearRule, String> {
+    let mut rule = GearRule::default();
+    if let Some(symbols) = args.value("symbols") {
+        rule.symbols = symbols.bytes().collect();
+    }
+    match (args.parsed("exactly")?, args.parsed("at-least")?) {
+        (Some(_), Some(_)) => return Err("Use either --exactly or --at-least".to_string()),
+        (Some(n), None) => rule.count = AdjacentCount::Exactly(n),
+        (None, Some(n)) => rule.count = AdjacentCount::AtLeast(n),
+        (None, None) => {}
+    }
+    if let Some(combine) = args.value("combine") {
+        rule.combine = combine.parse()?;
+    }
+    Ok(rule)
+}
+
+fn gears(args: &Args) -> Result<(), String> {
+    let rule = gear_rule(args)?;
+    let schematic = load_schematic(args)?;
+
+    if args.flag("list") {
+        for (i, ratio) in rule.gears(&schematic) {
+            let symbol = schematic.symbols[i];
+            println!(
+                "{} at row {} column {}: {ratio}",
+                symbol.symbol as char, symbol.row, symbol.column
+            );
+        }
+    }
+    println!("{}", rule.total(&schematic));
+    Ok(())
+}
+
+fn show(args: &Args) -> Result<(), String> {
+    let rule = gear_rule(args)?;
+    let schematic = load_schematic(args)?;
+    let mut view = View::default();
+    if args.flag("plain") {
+        view.style = Style::Plain;
+    }
+    if let Some(rows) = args.range("rows")? {
+        view.rows = rows;
+    }
+    if let Some(columns) = args.range("cols")? {
+        view.columns = columns;
+    }
+
+    println!("{}", legend(view.style));
+    print!("{}", render(&schematic, &rule, &view));
+    Ok(())
+}
+
+fn graph(args: &Args) -> Result<(), String> {
+    let schematic = load_schematic(args)?;
+    match args.value("format").unwrap_or("dot") {
+        "dot" => print!("{}", to_dot(&schematic)),
+        "json" => println!("{}", to_json(&schematic)),
+        format => return Err(format!("Unknown format `{format}`, expected dot or json")),
+    }
+    Ok(())
+}
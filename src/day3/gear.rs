@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+use super::EngineSchematic;
+
+/// How many numbers must touch a symbol for it to be a gear.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum AdjacentCount {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+/// How the numbers around a gear become its ratio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Combine {
+    Product,
+    Sum,
+    Max,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct GearRule {
+    pub symbols: Vec<u8>,
+    pub count: AdjacentCount,
+    pub combine: Combine,
+}
+
+impl Default for GearRule {
+    /// The gears of part two: a `*` touching exactly two numbers, with their
+    /// product as ratio.
+    fn default() -> Self {
+        GearRule {
+            symbols: vec![b'*'],
+            count: AdjacentCount::Exactly(2),
+            combine: Combine::Product,
+        }
+    }
+}
+
+impl FromStr for Combine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Combine::Product),
+            "sum" => Ok(Combine::Sum),
+            "max" => Ok(Combine::Max),
+            _ => Err(format!(
+                "Unknown combination `{s}`, expected product, sum or max"
+            )),
+        }
+    }
+}
+
+impl AdjacentCount {
+    fn accepts(self, count: usize) -> bool {
+        match self {
+            AdjacentCount::Exactly(n) => count == n,
+            AdjacentCount::AtLeast(n) => count >= n,
+        }
+    }
+}
+
+impl Combine {
+    fn apply(self, values: impl Iterator<Item = usize>) -> usize {
+        match self {
+            Combine::Product => values.product(),
+            Combine::Sum => values.sum(),
+            Combine::Max => values.max().unwrap_or_default(),
+        }
+    }
+}
+
+impl GearRule {
+    /// Index of every symbol that is a gear under the rule, with its ratio.
+    pub fn gears<'a>(
+        &'a self,
+        schematic: &'a EngineSchematic,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        schematic
+            .symbols
+            .iter()
+            .enumerate()
+            .filter(|(i, symbol)| {
+                self.symbols.contains(&symbol.symbol)
+                    && self.count.accepts(schematic.symbol_numbers[*i].len())
+            })
+            .map(|(i, _)| {
+                let ratio = self
+                    .combine
+                    .apply(schematic.adjacent_numbers(i).map(|n| n.value));
+                (i, ratio)
+            })
+    }
+
+    pub fn total(&self, schematic: &EngineSchematic) -> usize {
+        self.gears(schematic).map(|(_, ratio)| ratio).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn example() -> EngineSchematic {
+        fs::read_to_string("input/day3/example")
+            .expect("Need example file to test")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_default_rule_is_part2() {
+        assert_eq!(GearRule::default().total(&example()), 467835);
+    }
+
+    #[test]
+    fn test_rule_variants() {
+        let schematic = example();
+        let rule = GearRule {
+            symbols: b"*#+$".to_vec(),
+            count: AdjacentCount::AtLeast(1),
+            combine: Combine::Sum,
+        };
+        assert_eq!(rule.total(&schematic), 4361);
+
+        let rule = GearRule {
+            symbols: vec![b'*'],
+            count: AdjacentCount::Exactly(1),
+            combine: Combine::Max,
+        };
+        assert_eq!(rule.gears(&schematic).collect::<Vec<_>>(), [(2, 617)]);
+    }
+
+    #[test]
+    fn test_parse_combine() {
+        assert_eq!("max".parse(), Ok(Combine::Max));
+        assert!("min".parse::<Combine>().is_err());
+    }
+}
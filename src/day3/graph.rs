@@ -0,0 +1,124 @@
+use std::fmt::Write;
+
+use super::EngineSchematic;
+use crate::json::Value;
+
+/// The symbol to number adjacency as a JSON bipartite graph, number nodes
+/// have ids `n<index>` and symbol nodes `s<index>`.
+pub(super) fn to_json(schematic: &EngineSchematic) -> Value {
+    let numbers = schematic.numbers.iter().enumerate().map(|(i, n)| {
+        Value::object([
+            ("id", Value::from(format!("n{i}"))),
+            ("kind", Value::from("number")),
+            ("row", Value::from(n.row)),
+            ("start", Value::from(n.start)),
+            ("end", Value::from(n.end)),
+            ("value", Value::from(n.value)),
+        ])
+    });
+    let symbols = schematic.symbols.iter().enumerate().map(|(i, s)| {
+        Value::object([
+            ("id", Value::from(format!("s{i}"))),
+            ("kind", Value::from("symbol")),
+            ("row", Value::from(s.row)),
+            ("column", Value::from(s.column)),
+            ("symbol", Value::from((s.symbol as char).to_string())),
+        ])
+    });
+    let edges = schematic
+        .symbol_numbers
+        .iter()
+        .enumerate()
+        .flat_map(|(s, numbers)| {
+            numbers.iter().map(move |n| {
+                Value::object([
+                    ("source", Value::from(format!("s{s}"))),
+                    ("target", Value::from(format!("n{n}"))),
+                ])
+            })
+        });
+    Value::object([
+        ("nodes", Value::Array(numbers.chain(symbols).collect())),
+        ("edges", Value::Array(edges.collect())),
+    ])
+}
+
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The same graph in Graphviz DOT, nodes are pinned to their position in
+/// the schematic for `neato -n`.
+pub(super) fn to_dot(schematic: &EngineSchematic) -> String {
+    let mut dot = String::from("graph schematic {\n");
+    for (i, n) in schematic.numbers.iter().enumerate() {
+        writeln!(
+            dot,
+            "  n{i} [shape=box, label=\"{}\", pos=\"{},{}!\"];",
+            n.value,
+            n.start * 20,
+            -(n.row as i64) * 40
+        )
+        .unwrap();
+    }
+    for (i, s) in schematic.symbols.iter().enumerate() {
+        writeln!(
+            dot,
+            "  s{i} [shape=ellipse, label=\"{}\", pos=\"{},{}!\"];",
+            escape_dot(&(s.symbol as char).to_string()),
+            s.column * 20,
+            -(s.row as i64) * 40
+        )
+        .unwrap();
+    }
+    for (s, numbers) in schematic.symbol_numbers.iter().enumerate() {
+        for n in numbers {
+            writeln!(dot, "  s{s} -- n{n};").unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schematic() -> EngineSchematic {
+        "12.\n.\"*\n..3".parse().unwrap()
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            to_json(&schematic()).to_string(),
+            concat!(
+                r#"{"nodes":["#,
+                r#"{"id":"n0","kind":"number","row":0,"start":0,"end":2,"value":12},"#,
+                r#"{"id":"n1","kind":"number","row":2,"start":2,"end":3,"value":3},"#,
+                r#"{"id":"s0","kind":"symbol","row":1,"column":1,"symbol":"\""},"#,
+                r#"{"id":"s1","kind":"symbol","row":1,"column":2,"symbol":"*"}],"#,
+                r#""edges":[{"source":"s0","target":"n0"},{"source":"s0","target":"n1"},"#,
+                r#"{"source":"s1","target":"n0"},{"source":"s1","target":"n1"}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(
+            to_dot(&schematic()),
+            "graph schematic {
+  n0 [shape=box, label=\"12\", pos=\"0,0!\"];
+  n1 [shape=box, label=\"3\", pos=\"40,-80!\"];
+  s0 [shape=ellipse, label=\"\\\"\", pos=\"20,-40!\"];
+  s1 [shape=ellipse, label=\"*\", pos=\"40,-40!\"];
+  s0 -- n0;
+  s0 -- n1;
+  s1 -- n0;
+  s1 -- n1;
+}
+"
+        );
+    }
+}
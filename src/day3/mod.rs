@@ -1,86 +1,159 @@
-fn extract_digits_around_position(data: &[u8], column: usize) -> (usize, usize) {
-    let mut digits_before: Vec<u8> = data
-        .iter()
-        .take(column)
-        .rev()
-        .take_while(|&c| c.is_ascii_digit())
-        .cloned()
-        .collect();
-
-    let digits_after: Vec<u8> = data
-        .iter()
-        .skip(column + 1)
-        .take_while(|&c| c.is_ascii_digit())
-        .cloned()
-        .collect();
-
-    let start = column - digits_before.len();
-
-    // Combine the two sets of digits
-    digits_before.reverse();
-    let mut result = digits_before;
-    result.push(data[column]);
-    result.extend(digits_after);
-
-    (
-        start,
-        std::str::from_utf8(&result)
-            .expect("We only take the digits, so this is fine")
-            .parse()
-            .unwrap(),
-    )
+use std::str::FromStr;
+
+pub(crate) mod cli;
+mod gear;
+mod graph;
+mod render;
+mod validate;
+
+use gear::GearRule;
+
+/// A number of the schematic, identified by its row and the columns it
+/// spans, `end` exclusive.
+#[derive(Debug, Clone, Copy)]
+struct NumberToken {
+    row: usize,
+    start: usize,
+    end: usize,
+    value: usize,
 }
 
-fn find_numbers(data: &[Vec<u8>], position: (usize, usize)) -> Vec<usize> {
-    let lookup_matrix = [
-        (1, 1),
-        (1, 0),
-        (1, -1),
-        (0, 1),
-        (0, -1),
-        (-1, 1),
-        (-1, 0),
-        (-1, -1),
-    ];
-
-    let mut v: Vec<_> = lookup_matrix
-        .into_iter()
-        .filter_map(|(l, c)| {
-            position
-                .0
-                .checked_add_signed(l)
-                .zip(position.1.checked_add_signed(c))
-        })
-        .filter(|(l, c)| {
-            data.get(*l)
-                .and_then(|line| line.get(*c))
-                .map(|c| c.is_ascii_digit())
-                .unwrap_or_default()
-        })
-        .map(|(l, c)| extract_digits_around_position(data.get(l).unwrap(), c))
-        .collect();
+impl PartialEq for NumberToken {
+    fn eq(&self, other: &Self) -> bool {
+        (self.row, self.start, self.end) == (other.row, other.start, other.end)
+    }
+}
 
-    v.dedup();
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Symbol {
+    row: usize,
+    column: usize,
+    symbol: u8,
+}
 
-    v.into_iter().map(|(_, value)| value).collect()
+/// The schematic tokenized into numbers and symbols, with the adjacency
+/// between them indexed both ways.
+#[derive(Debug)]
+struct EngineSchematic {
+    grid: Vec<Vec<u8>>,
+    numbers: Vec<NumberToken>,
+    symbols: Vec<Symbol>,
+    /// Indexes of the numbers adjacent to each symbol
+    symbol_numbers: Vec<Vec<usize>>,
+    /// Indexes of the symbols adjacent to each number
+    number_symbols: Vec<Vec<usize>>,
 }
 
-pub(crate) mod part1 {
-    use super::*;
+impl FromStr for EngineSchematic {
+    type Err = String;
 
-    pub fn resolve(input: &str) -> String {
-        let data: Vec<Vec<u8>> = input.lines().map(|c| c.as_bytes().to_vec()).collect();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut numbers = vec![];
+        let mut symbols = vec![];
+        // Which number, if any, covers each cell
+        let mut cells: Vec<Vec<Option<usize>>> = vec![];
+        let grid: Vec<Vec<u8>> = s.lines().map(|l| l.as_bytes().to_vec()).collect();
+
+        for (row, line) in grid.iter().enumerate() {
+            let mut row_cells = vec![None; line.len()];
+            let mut column = 0;
+            while column < line.len() {
+                let c = line[column];
+                if c.is_ascii_digit() {
+                    let end = line[column..]
+                        .iter()
+                        .position(|c| !c.is_ascii_digit())
+                        .map_or(line.len(), |len| column + len);
+                    let value = std::str::from_utf8(&line[column..end])
+                        .expect("We only take the digits, so this is fine")
+                        .parse()
+                        .map_err(|_| format!("Number at row {row} column {column} is too big"))?;
+                    row_cells[column..end].fill(Some(numbers.len()));
+                    numbers.push(NumberToken {
+                        row,
+                        start: column,
+                        end,
+                        value,
+                    });
+                    column = end;
+                    continue;
+                }
+                if c != b'.' {
+                    symbols.push(Symbol {
+                        row,
+                        column,
+                        symbol: c,
+                    });
+                }
+                column += 1;
+            }
+            cells.push(row_cells);
+        }
 
-        let mut sum = 0;
-        for (line, content) in data.iter().enumerate() {
-            for (column, c) in content.iter().enumerate() {
-                if !(c.is_ascii_digit() || *c == b'.') {
-                    sum += find_numbers(&data, (line, column)).iter().sum::<usize>();
+        let symbol_numbers: Vec<Vec<usize>> = symbols
+            .iter()
+            .map(|s| {
+                let mut adjacent = vec![];
+                for row in s.row.saturating_sub(1)..=s.row + 1 {
+                    for column in s.column.saturating_sub(1)..=s.column + 1 {
+                        let number = cells.get(row).and_then(|r| r.get(column)).copied();
+                        // Many neighbours may be digits of the same number
+                        if let Some(Some(i)) = number {
+                            if !adjacent.contains(&i) {
+                                adjacent.push(i);
+                            }
+                        }
+                    }
                 }
+                adjacent
+            })
+            .collect();
+
+        let mut number_symbols = vec![vec![]; numbers.len()];
+        for (symbol, adjacent) in symbol_numbers.iter().enumerate() {
+            for &number in adjacent {
+                number_symbols[number].push(symbol);
             }
         }
 
-        sum.to_string()
+        Ok(EngineSchematic {
+            grid,
+            numbers,
+            symbols,
+            symbol_numbers,
+            number_symbols,
+        })
+    }
+}
+
+impl EngineSchematic {
+    /// Numbers adjacent to at least one symbol.
+    fn part_numbers(&self) -> impl Iterator<Item = &NumberToken> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    fn adjacent_numbers(&self, symbol: usize) -> impl Iterator<Item = &NumberToken> {
+        self.symbol_numbers[symbol]
+            .iter()
+            .map(|&i| &self.numbers[i])
+    }
+}
+
+pub(crate) mod part1 {
+    use super::*;
+
+    pub fn resolve(input: &str) -> String {
+        let schematic: EngineSchematic = input.parse().expect("Invalid input");
+
+        schematic
+            .part_numbers()
+            .map(|n| n.value)
+            .sum::<usize>()
+            .to_string()
     }
 
     #[cfg(test)]
@@ -102,21 +175,9 @@ pub(crate) mod part2 {
     use super::*;
 
     pub fn resolve(input: &str) -> String {
-        let data: Vec<Vec<u8>> = input.lines().map(|c| c.as_bytes().to_vec()).collect();
-
-        let mut sum = 0;
-        for (line, content) in data.iter().enumerate() {
-            for (column, c) in content.iter().enumerate() {
-                if *c == b'*' {
-                    let numbers = find_numbers(&data, (line, column));
-                    if numbers.len() == 2 {
-                        sum += numbers.iter().product::<usize>();
-                    }
-                }
-            }
-        }
+        let schematic: EngineSchematic = input.parse().expect("Invalid input");
 
-        sum.to_string()
+        GearRule::default().total(&schematic).to_string()
     }
 
     #[cfg(test)]
@@ -133,3 +194,85 @@ pub(crate) mod part2 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(schematic: &EngineSchematic, symbol: usize) -> Vec<usize> {
+        schematic
+            .adjacent_numbers(symbol)
+            .map(|n| n.value)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let schematic: EngineSchematic = "467..114..\n...*......\n..35..633.".parse().unwrap();
+        assert_eq!(
+            schematic.numbers,
+            [
+                NumberToken {
+                    row: 0,
+                    start: 0,
+                    end: 3,
+                    value: 467
+                },
+                NumberToken {
+                    row: 0,
+                    start: 5,
+                    end: 8,
+                    value: 114
+                },
+                NumberToken {
+                    row: 2,
+                    start: 2,
+                    end: 4,
+                    value: 35
+                },
+                NumberToken {
+                    row: 2,
+                    start: 6,
+                    end: 9,
+                    value: 633
+                },
+            ]
+        );
+        assert_eq!(
+            schematic.symbols,
+            [Symbol {
+                row: 1,
+                column: 3,
+                symbol: b'*'
+            }]
+        );
+        assert_eq!(schematic.symbol_numbers, [vec![0, 2]]);
+        assert_eq!(schematic.number_symbols, [vec![0], vec![], vec![0], vec![]]);
+    }
+
+    #[test]
+    fn test_same_number_on_different_rows() {
+        let schematic: EngineSchematic = ".5.\n.*.\n.5.".parse().unwrap();
+        assert_eq!(values(&schematic, 0), [5, 5]);
+        assert_eq!(part1::resolve(".5.\n.*.\n.5."), "10");
+        assert_eq!(part2::resolve(".5.\n.*.\n.5."), "25");
+    }
+
+    #[test]
+    fn test_number_touching_many_neighbours_counted_once() {
+        let schematic: EngineSchematic = "123\n.*.\n4.5".parse().unwrap();
+        assert_eq!(values(&schematic, 0), [123, 4, 5]);
+    }
+
+    #[test]
+    fn test_equal_numbers_on_same_row() {
+        let schematic: EngineSchematic = "7.7\n.#.\n...".parse().unwrap();
+        assert_eq!(values(&schematic, 0), [7, 7]);
+    }
+
+    #[test]
+    fn test_number_next_to_two_symbols_is_one_part() {
+        assert_eq!(part1::resolve("#12#"), "12");
+        assert_eq!(part1::resolve("12\n..\n34"), "0");
+    }
+}
@@ -0,0 +1,191 @@
+use std::ops::RangeInclusive;
+
+use super::{gear::GearRule, EngineSchematic};
+
+/// What a cell of the schematic holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Cell {
+    Empty,
+    PartNumber,
+    OtherNumber,
+    Symbol,
+    Gear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Style {
+    /// Cells coloured with ANSI escape codes.
+    Ansi,
+    /// Every row followed by a line of markers under its cells.
+    Plain,
+}
+
+/// Part of the schematic to render, ranges past the schematic are clipped.
+pub(super) struct View {
+    pub style: Style,
+    pub rows: RangeInclusive<usize>,
+    pub columns: RangeInclusive<usize>,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            style: Style::Ansi,
+            rows: 0..=usize::MAX,
+            columns: 0..=usize::MAX,
+        }
+    }
+}
+
+impl Cell {
+    fn ansi(self) -> &'static str {
+        match self {
+            Cell::Empty => "\x1b[2m",
+            Cell::PartNumber => "\x1b[32m",
+            Cell::OtherNumber => "\x1b[31m",
+            Cell::Symbol => "\x1b[33m",
+            Cell::Gear => "\x1b[1;35m",
+        }
+    }
+
+    fn marker(self) -> char {
+        match self {
+            Cell::Empty => ' ',
+            Cell::PartNumber => '^',
+            Cell::OtherNumber => '-',
+            Cell::Symbol => 's',
+            Cell::Gear => 'G',
+        }
+    }
+}
+
+pub(super) fn legend(style: Style) -> String {
+    let cells = [
+        (Cell::PartNumber, "part number"),
+        (Cell::OtherNumber, "other number"),
+        (Cell::Symbol, "symbol"),
+        (Cell::Gear, "gear"),
+    ];
+    let entries: Vec<String> = cells
+        .iter()
+        .map(|(cell, name)| match style {
+            Style::Ansi => format!("{}{name}\x1b[0m", cell.ansi()),
+            Style::Plain => format!("{} {name}", cell.marker()),
+        })
+        .collect();
+    entries.join(", ")
+}
+
+fn classify(schematic: &EngineSchematic, rule: &GearRule) -> Vec<Vec<Cell>> {
+    let mut cells: Vec<Vec<Cell>> = schematic
+        .grid
+        .iter()
+        .map(|row| vec![Cell::Empty; row.len()])
+        .collect();
+    for (number, symbols) in schematic.numbers.iter().zip(&schematic.number_symbols) {
+        let cell = if symbols.is_empty() {
+            Cell::OtherNumber
+        } else {
+            Cell::PartNumber
+        };
+        cells[number.row][number.start..number.end].fill(cell);
+    }
+    for symbol in schematic.symbols.iter() {
+        cells[symbol.row][symbol.column] = Cell::Symbol;
+    }
+    for (i, _) in rule.gears(schematic) {
+        let symbol = schematic.symbols[i];
+        cells[symbol.row][symbol.column] = Cell::Gear;
+    }
+    cells
+}
+
+pub(super) fn render(schematic: &EngineSchematic, rule: &GearRule, view: &View) -> String {
+    let cells = classify(schematic, rule);
+    let mut output = String::new();
+    for (row, (line, kinds)) in schematic.grid.iter().zip(&cells).enumerate() {
+        if !view.rows.contains(&row) {
+            continue;
+        }
+        let visible: Vec<(char, Cell)> = line
+            .iter()
+            .zip(kinds)
+            .enumerate()
+            .filter(|(column, _)| view.columns.contains(column))
+            .map(|(_, (&c, &kind))| (c as char, kind))
+            .collect();
+
+        match view.style {
+            Style::Ansi => {
+                let mut current = None;
+                for &(c, kind) in visible.iter() {
+                    if current != Some(kind) {
+                        if current.is_some() {
+                            output.push_str("\x1b[0m");
+                        }
+                        output.push_str(kind.ansi());
+                        current = Some(kind);
+                    }
+                    output.push(c);
+                }
+                output.push_str("\x1b[0m\n");
+            }
+            Style::Plain => {
+                output.extend(visible.iter().map(|(c, _)| c));
+                output.push('\n');
+                let markers: String = visible.iter().map(|(_, kind)| kind.marker()).collect();
+                if !markers.trim_end().is_empty() {
+                    output.push_str(markers.trim_end());
+                    output.push('\n');
+                }
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schematic() -> EngineSchematic {
+        "467..114..\n...*......\n..35..633.".parse().unwrap()
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let view = View {
+            style: Style::Plain,
+            ..View::default()
+        };
+        let rule = GearRule::default();
+        assert_eq!(
+            render(&schematic(), &rule, &view),
+            "467..114..\n^^^  ---\n...*......\n   G\n..35..633.\n  ^^  ---\n"
+        );
+    }
+
+    #[test]
+    fn test_render_gear_window() {
+        let schematic: EngineSchematic = "1.2\n.*.\n...".parse().unwrap();
+        let view = View {
+            style: Style::Plain,
+            rows: 0..=1,
+            columns: 1..=2,
+        };
+        assert_eq!(
+            render(&schematic, &GearRule::default(), &view),
+            ".2\n ^\n*.\nG\n"
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let schematic: EngineSchematic = "1*".parse().unwrap();
+        let rule = GearRule::default();
+        assert_eq!(
+            render(&schematic, &rule, &View::default()),
+            "\x1b[32m1\x1b[0m\x1b[33m*\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt::Display;
+
+/// Something in a schematic the solver would silently misread.
+#[derive(Debug, PartialEq)]
+pub(super) enum Issue {
+    /// Row narrower than the widest row of the schematic.
+    RaggedRow {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    NonAscii {
+        row: usize,
+        column: usize,
+        c: char,
+    },
+    /// Row ending with `\r`, as in CRLF line endings.
+    CarriageReturn {
+        row: usize,
+    },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::RaggedRow {
+                row,
+                width,
+                expected,
+            } => write!(f, "row {row} has {width} columns, expected {expected}"),
+            Issue::NonAscii { row, column, c } => {
+                write!(f, "row {row} column {column} has non-ASCII {c:?}")
+            }
+            Issue::CarriageReturn { row } => write!(f, "row {row} ends with a carriage return"),
+        }
+    }
+}
+
+/// Fixes applied to a schematic before solving it.
+#[derive(Debug, Default)]
+pub(super) struct Normalization {
+    /// Remove the `\r` at the end of the rows.
+    pub strip_cr: bool,
+    /// Fill the narrower rows with `.` up to the widest row.
+    pub pad: bool,
+    /// Replace every non-ASCII character with this one.
+    pub replace_non_ascii: Option<char>,
+}
+
+/// Rows of the schematic, without the line feed, a trailing line feed
+/// does not start a new row.
+fn rows(input: &str) -> impl Iterator<Item = &str> {
+    input.strip_suffix('\n').unwrap_or(input).split('\n')
+}
+
+fn width(row: &str) -> usize {
+    row.strip_suffix('\r').unwrap_or(row).chars().count()
+}
+
+pub(super) fn validate(input: &str) -> Vec<Issue> {
+    if input.is_empty() {
+        return vec![];
+    }
+    let expected = rows(input).map(width).max().unwrap_or_default();
+    let mut issues = vec![];
+    for (row, line) in rows(input).enumerate() {
+        let content = match line.strip_suffix('\r') {
+            Some(content) => {
+                issues.push(Issue::CarriageReturn { row });
+                content
+            }
+            None => line,
+        };
+        for (column, c) in content.chars().enumerate() {
+            if !c.is_ascii() {
+                issues.push(Issue::NonAscii { row, column, c });
+            }
+        }
+        let width = content.chars().count();
+        if width != expected {
+            issues.push(Issue::RaggedRow {
+                row,
+                width,
+                expected,
+            });
+        }
+    }
+    issues
+}
+
+pub(super) fn normalize(input: &str, normalization: &Normalization) -> String {
+    if input.is_empty() {
+        return String::new();
+    }
+    let expected = rows(input).map(width).max().unwrap_or_default();
+    let mut output = String::with_capacity(input.len());
+    for line in rows(input) {
+        let (content, cr) = match line.strip_suffix('\r') {
+            Some(content) if normalization.strip_cr => (content, ""),
+            Some(content) => (content, "\r"),
+            None => (line, ""),
+        };
+        let mut width = 0;
+        for c in content.chars() {
+            match normalization.replace_non_ascii {
+                Some(replacement) if !c.is_ascii() => output.push(replacement),
+                _ => output.push(c),
+            }
+            width += 1;
+        }
+        if normalization.pad {
+            output.extend(std::iter::repeat_n('.', expected - width));
+        }
+        output.push_str(cr);
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let input = "12.\r\n.é\n..*\n";
+        assert_eq!(
+            validate(input),
+            [
+                Issue::CarriageReturn { row: 0 },
+                Issue::NonAscii {
+                    row: 1,
+                    column: 1,
+                    c: 'é'
+                },
+                Issue::RaggedRow {
+                    row: 1,
+                    width: 2,
+                    expected: 3
+                },
+            ]
+        );
+        assert!(validate("...\n.*.\n...").is_empty());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let input = "12.\r\n.é\n..*\n";
+        let normalization = Normalization {
+            strip_cr: true,
+            pad: true,
+            replace_non_ascii: Some('#'),
+        };
+        let normalized = normalize(input, &normalization);
+        assert_eq!(normalized, "12.\n.#.\n..*\n");
+        assert!(validate(&normalized).is_empty());
+        assert_eq!(normalize(input, &Normalization::default()), input);
+    }
+}
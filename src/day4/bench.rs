@@ -0,0 +1,173 @@
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use super::ScratchCard;
+use crate::rng::Rng;
+
+/// Size of a generated deck, numbers are drawn from `1..=max`.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct DeckShape {
+    pub cards: usize,
+    pub winning: usize,
+    pub ticket: usize,
+    pub max: usize,
+}
+
+impl Default for DeckShape {
+    /// Same shape as the puzzle input.
+    fn default() -> Self {
+        DeckShape {
+            cards: 200,
+            winning: 10,
+            ticket: 25,
+            max: 99,
+        }
+    }
+}
+
+fn distinct_numbers(rng: &mut Rng, count: usize, max: usize) -> Vec<usize> {
+    let mut seen = HashSet::with_capacity(count);
+    let mut numbers = Vec::with_capacity(count);
+    while numbers.len() < count {
+        let n = rng.range(1..max + 1);
+        if seen.insert(n) {
+            numbers.push(n);
+        }
+    }
+    numbers
+}
+
+/// Random deck in the puzzle syntax, without duplicates inside a list.
+pub(super) fn random_deck(rng: &mut Rng, shape: &DeckShape) -> Result<String, String> {
+    if shape.winning.max(shape.ticket) > shape.max {
+        return Err(format!(
+            "Cannot draw {} distinct numbers up to {}",
+            shape.winning.max(shape.ticket),
+            shape.max
+        ));
+    }
+    // Numbers are drawn from `1..max + 1`
+    if shape.max == usize::MAX {
+        return Err(format!("Numbers must stay below {}", usize::MAX));
+    }
+    let mut deck = String::new();
+    for id in 1..=shape.cards {
+        let list = |numbers: Vec<usize>| {
+            numbers
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let winning = list(distinct_numbers(rng, shape.winning, shape.max));
+        let ticket = list(distinct_numbers(rng, shape.ticket, shape.max));
+        writeln!(deck, "Card {id}: {winning} | {ticket}").unwrap();
+    }
+    Ok(deck)
+}
+
+/// Cards as two hash sets, the representation before the bitset.
+fn hash_cards(input: &str) -> Vec<(HashSet<usize>, HashSet<usize>)> {
+    let numbers = |list: &str| {
+        list.split_ascii_whitespace()
+            .filter_map(|n| n.parse().ok())
+            .collect()
+    };
+    input
+        .lines()
+        .filter_map(|line| line.split_once(": ")?.1.split_once(" | "))
+        .map(|(winning, ticket)| (numbers(winning), numbers(ticket)))
+        .collect()
+}
+
+/// Time spent counting the matches of a deck with both representations.
+#[derive(Debug)]
+pub(super) struct Comparison {
+    pub cards: usize,
+    pub matches: usize,
+    pub bitset: Duration,
+    pub hashset: Duration,
+}
+
+/// Counts every match of the deck `rounds` times with each representation,
+/// parsing is not timed.
+pub(super) fn compare(input: &str, rounds: usize) -> Result<Comparison, String> {
+    let cards = input
+        .lines()
+        .map(|line| line.parse::<ScratchCard>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid deck: {e:?}"))?;
+    let hashed = hash_cards(input);
+
+    let start = Instant::now();
+    let mut matches = 0;
+    for _ in 0..rounds {
+        matches = black_box(&cards).iter().map(ScratchCard::matches).sum();
+    }
+    let bitset = start.elapsed();
+
+    let start = Instant::now();
+    let mut hash_matches = 0;
+    for _ in 0..rounds {
+        hash_matches = black_box(&hashed)
+            .iter()
+            .map(|(winning, ticket)| winning.intersection(ticket).count())
+            .sum();
+    }
+    let hashset = start.elapsed();
+
+    if matches != hash_matches {
+        return Err(format!(
+            "Representations disagree: {matches} matches with the bitset, {hash_matches} with hash sets"
+        ));
+    }
+    Ok(Comparison {
+        cards: cards.len(),
+        matches,
+        bitset,
+        hashset,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_compare_example() {
+        let input = fs::read_to_string("input/day4/example").expect("Need example file to test");
+        let comparison = compare(&input, 1).unwrap();
+        assert_eq!(comparison.cards, 6);
+        assert_eq!(comparison.matches, 4 + 2 + 2 + 1);
+    }
+
+    #[test]
+    fn test_random_decks_agree() {
+        let mut rng = Rng::new(4);
+        for max in [99, 127, 128, 100_000] {
+            let shape = DeckShape {
+                cards: 50,
+                max,
+                ..DeckShape::default()
+            };
+            let deck = random_deck(&mut rng, &shape).unwrap();
+            let comparison = compare(&deck, 1).unwrap();
+            assert_eq!(comparison.cards, 50);
+        }
+        let too_small = DeckShape {
+            max: 20,
+            ..DeckShape::default()
+        };
+        assert!(random_deck(&mut rng, &too_small).is_err());
+        let too_large = DeckShape {
+            max: usize::MAX,
+            ..DeckShape::default()
+        };
+        assert!(random_deck(&mut rng, &too_large).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+use std::{fmt::Display, str::FromStr};
+
+/// Amount of card copies, `usize` fails on overflow while [`BigCount`]
+/// never does.
+pub(super) trait Count: Clone + Ord + Display {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+impl Count for usize {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        usize::checked_add(*self, *other)
+    }
+}
+
+/// Arbitrary precision unsigned integer, only supporting addition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct BigCount {
+    /// Base 10^9 digits, least significant first, no trailing zeros.
+    limbs: Vec<u32>,
+}
+
+const LIMB_BASE: u32 = 1_000_000_000;
+
+impl Count for BigCount {
+    fn zero() -> Self {
+        BigCount { limbs: vec![] }
+    }
+
+    fn one() -> Self {
+        BigCount { limbs: vec![1] }
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let sum = self.limbs.get(i).unwrap_or(&0) + other.limbs.get(i).unwrap_or(&0) + carry;
+            limbs.push(sum % LIMB_BASE);
+            carry = sum / LIMB_BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+        Some(BigCount { limbs })
+    }
+}
+
+impl Ord for BigCount {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigCount {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((most, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+        write!(f, "{most}")?;
+        for limb in rest.iter().rev() {
+            write!(f, "{limb:09}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What to do when a card wins copies of cards past the end of the deck.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum OutOfRange {
+    /// Ignore the copies past the end.
+    Clamp,
+    /// Fail the simulation.
+    Error,
+    /// Add the missing cards to the deck, without an original copy and
+    /// without any match.
+    Extend,
+}
+
+impl FromStr for OutOfRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(OutOfRange::Clamp),
+            "error" => Ok(OutOfRange::Error),
+            "extend" => Ok(OutOfRange::Extend),
+            _ => Err(format!(
+                "Unknown policy `{s}`, expected clamp, error or extend"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum CascadeError {
+    /// The card wins copies of cards past the end of the deck.
+    OutOfRange { card: usize, matches: usize },
+    /// Copies of the card, or copies it granted, do not fit in the count
+    /// type.
+    Overflow { card: usize },
+    /// Every card fits in the count type, but not their total.
+    TotalOverflow,
+}
+
+impl Display for CascadeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CascadeError::OutOfRange { card, matches } => write!(
+                f,
+                "card {} wins {matches} copies past the end of the deck",
+                card + 1
+            ),
+            CascadeError::Overflow { card } => {
+                write!(f, "counts of card {} overflow, try a big count", card + 1)
+            }
+            CascadeError::TotalOverflow => {
+                write!(f, "total amount of cards overflows, try a big count")
+            }
+        }
+    }
+}
+
+/// What happened to a single card during the cascade of part two.
+#[derive(Debug, PartialEq)]
+pub(super) struct CardOutcome<C> {
+    pub matches: usize,
+    /// Instances of the card at the end, the original included.
+    pub copies: C,
+    /// Copies this card gave to the others.
+    pub granted: C,
+    /// Cards that granted copies of this one, with how many copies each.
+    pub granted_by: Vec<(usize, C)>,
+}
+
+/// A card giving copies of another one, cards are indexes in the deck.
+#[derive(Debug, PartialEq)]
+pub(super) struct Step<C> {
+    pub from: usize,
+    pub to: usize,
+    pub copies: C,
+}
+
+/// Result of playing the whole deck, every card winning copies of the
+/// cards after it.
+#[derive(Debug, PartialEq)]
+pub(super) struct Cascade<C> {
+    pub cards: Vec<CardOutcome<C>>,
+    pub trace: Vec<Step<C>>,
+    /// Total amount of cards at the end.
+    pub total: C,
+}
+
+impl<C: Count> Cascade<C> {
+    /// Plays the deck given the match count of every card.
+    pub fn simulate(matches: &[usize], policy: OutOfRange) -> Result<Self, CascadeError> {
+        Self::simulate_capped(matches, policy, None)
+    }
+
+    /// Same as [`Cascade::simulate`], but a card never has more than `cap`
+    /// copies, the copies won above it are lost.
+    pub fn simulate_capped(
+        matches: &[usize],
+        policy: OutOfRange,
+        cap: Option<&C>,
+    ) -> Result<Self, CascadeError> {
+        let card = |matches, copies| CardOutcome {
+            matches,
+            copies,
+            granted: C::zero(),
+            granted_by: vec![],
+        };
+        let mut cards: Vec<CardOutcome<C>> = matches
+            .iter()
+            .map(|&matches| card(matches, C::one()))
+            .collect();
+        let mut trace = vec![];
+
+        let mut from = 0;
+        while from < cards.len() {
+            let copies = cards[from].copies.clone();
+            let mut last = from + cards[from].matches;
+            if last >= cards.len() {
+                match policy {
+                    OutOfRange::Clamp => last = cards.len() - 1,
+                    OutOfRange::Error => {
+                        return Err(CascadeError::OutOfRange {
+                            card: from,
+                            matches: cards[from].matches,
+                        })
+                    }
+                    OutOfRange::Extend => cards.resize_with(last + 1, || card(0, C::zero())),
+                }
+            }
+            for to in from + 1..=last {
+                let overflow = || CascadeError::Overflow { card: to };
+                cards[to].copies = cards[to].copies.checked_add(&copies).ok_or_else(overflow)?;
+                if let Some(cap) = cap.filter(|&cap| &cards[to].copies > cap) {
+                    cards[to].copies = cap.clone();
+                }
+                cards[from].granted = cards[from]
+                    .granted
+                    .checked_add(&copies)
+                    .ok_or(CascadeError::Overflow { card: from })?;
+                cards[to].granted_by.push((from, copies.clone()));
+                trace.push(Step {
+                    from,
+                    to,
+                    copies: copies.clone(),
+                });
+            }
+            from += 1;
+        }
+
+        let total = cards.iter().try_fold(C::zero(), |total, card| {
+            total
+                .checked_add(&card.copies)
+                .ok_or(CascadeError::TotalOverflow)
+        })?;
+        Ok(Cascade {
+            cards,
+            trace,
+            total,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_cascade() {
+        let cascade = Cascade::<usize>::simulate(&[4, 2, 2, 1, 0, 0], OutOfRange::Error).unwrap();
+        assert_eq!(cascade.total, 30);
+        let copies: Vec<_> = cascade.cards.iter().map(|c| c.copies).collect();
+        assert_eq!(copies, [1, 2, 4, 8, 14, 1]);
+        assert_eq!(cascade.cards[3].granted_by, [(0, 1), (1, 2), (2, 4)]);
+        let granted: Vec<_> = cascade.cards.iter().map(|c| c.granted).collect();
+        assert_eq!(granted, [4, 4, 8, 8, 0, 0]);
+        assert_eq!(
+            cascade.trace[..2],
+            [
+                Step {
+                    from: 0,
+                    to: 1,
+                    copies: 1
+                },
+                Step {
+                    from: 0,
+                    to: 2,
+                    copies: 1
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_policies() {
+        let matches = [1, 3, 0];
+        assert_eq!(
+            Cascade::<usize>::simulate(&matches, OutOfRange::Error),
+            Err(CascadeError::OutOfRange {
+                card: 1,
+                matches: 3
+            })
+        );
+
+        let clamped = Cascade::<usize>::simulate(&matches, OutOfRange::Clamp).unwrap();
+        assert_eq!(clamped.total, 1 + 2 + 3);
+
+        let extended = Cascade::<usize>::simulate(&matches, OutOfRange::Extend).unwrap();
+        let copies: Vec<_> = extended.cards.iter().map(|c| c.copies).collect();
+        assert_eq!(copies, [1, 2, 3, 2, 2]);
+        assert_eq!(extended.total, 10);
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Every card wins all the cards after it, doubling the copies
+        let matches: Vec<usize> = (0..70).rev().collect();
+        assert_eq!(
+            Cascade::<usize>::simulate(&matches, OutOfRange::Clamp),
+            Err(CascadeError::Overflow { card: 61 })
+        );
+        let cascade = Cascade::<BigCount>::simulate(&matches, OutOfRange::Clamp).unwrap();
+        assert_eq!(cascade.total.to_string(), "1180591620717411303423");
+
+        // Every count fits, the extra first card pushes the total past
+        let bits = usize::BITS as usize;
+        let matches: Vec<usize> = [0].into_iter().chain((0..bits).rev()).collect();
+        assert_eq!(
+            Cascade::<usize>::simulate(&matches, OutOfRange::Clamp),
+            Err(CascadeError::TotalOverflow)
+        );
+    }
+
+    #[test]
+    fn test_big_count_display() {
+        let big = (0..3).fold(BigCount::one(), |acc, _| {
+            (0..1000).fold(BigCount::zero(), |sum, _| sum.checked_add(&acc).unwrap())
+        });
+        assert_eq!(big.to_string(), "1000000000");
+        assert_eq!(BigCount::zero().to_string(), "0");
+        assert!(big > BigCount::one().checked_add(&BigCount::one()).unwrap());
+    }
+}
@@ -0,0 +1,186 @@
+use super::{
+    bench::{compare, random_deck, DeckShape},
+    cascade::{BigCount, Cascade, Count},
+    *,
+};
+use crate::{cli::Args, rng::Rng};
+
+const USAGE: &str = "Usage: day4 <command> [--input FILE] [--reorder]
+Cards must be listed in the order of their ids, unless --reorder sorts them.
+Commands:
+  check
+      List the duplicate, missing and out of order card ids.
+  report [--top N] [--cards] [--trace] [--policy clamp|error|extend] [--big]
+      Total of part two and the N cards (5 by default) that granted the
+      most copies.
+      --policy what to do with copies won past the end of the deck, error
+               by default
+      --big    count with arbitrary precision instead of failing on overflow
+      --cards  also print the matches, copies and granters of every card
+      --trace  also print every step of the cascade
+  bench [--deck N] [--winning N] [--ticket N] [--max N] [--seed S] [--rounds N]
+      Time match counting with the bitset against hash sets on a random
+      deck of N cards, shaped like the puzzle input by default. Numbers above
+      127 use the fallback set of the bitset.
+  compare [--rules LIST] [--policy clamp|error|extend]
+      Score of the deck under every rule of the comma separated list:
+      doubling, linear, cascade, capped:N and backward, all of them by
+      default. Policy applies to copies won past either end of the deck,
+      clamp by default.";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &["cards", "trace", "big", "reorder"],
+        &[
+            "input", "top", "policy", "deck", "winning", "ticket", "max", "seed", "rounds", "rules",
+        ],
+    )?;
+    match args.positional() {
+        [command] if command == "check" => check(&args),
+        [command] if command == "report" => report(&args),
+        [command] if command == "bench" => bench(&args),
+        [command] if command == "compare" => compare_rules(&args),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn parse_cards(input: &str) -> Result<Vec<ScratchCard>, String> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|e| format!("Invalid card on line {}: {e}", i + 1))
+        })
+        .collect()
+}
+
+/// Cards of the input, in the order of their ids.
+fn load_cards(args: &Args) -> Result<Vec<ScratchCard>, String> {
+    let cards = parse_cards(&args.input(4)?)?;
+    deck::arrange(cards, args.flag("reorder")).map_err(|issues| {
+        let issues: Vec<String> = issues.iter().map(|i| format!("  {i}")).collect();
+        format!("Invalid card ids:\n{}", issues.join("\n"))
+    })
+}
+
+fn check(args: &Args) -> Result<(), String> {
+    let cards = parse_cards(&args.input(4)?)?;
+    let issues = deck::check_ids(&cards, !args.flag("reorder"));
+    for issue in issues.iter() {
+        println!("{issue}");
+    }
+    if issues.is_empty() {
+        println!("{} cards, ids are valid", cards.len());
+        Ok(())
+    } else {
+        Err(format!("{} issues found", issues.len()))
+    }
+}
+
+fn report(args: &Args) -> Result<(), String> {
+    if args.flag("big") {
+        report_with::<BigCount>(args)
+    } else {
+        report_with::<usize>(args)
+    }
+}
+
+fn report_with<C: Count>(args: &Args) -> Result<(), String> {
+    let top = args.parsed("top")?.unwrap_or(5);
+    let policy: OutOfRange = args.value("policy").unwrap_or("error").parse()?;
+    let cards = load_cards(args)?;
+    let matches: Vec<usize> = cards.iter().map(ScratchCard::matches).collect();
+    let cascade = Cascade::<C>::simulate(&matches, policy).map_err(|e| e.to_string())?;
+
+    if args.flag("trace") {
+        for step in cascade.trace.iter() {
+            println!(
+                "Card {} gives {} copies of card {}",
+                step.from + 1,
+                step.copies,
+                step.to + 1
+            );
+        }
+    }
+    if args.flag("cards") {
+        for (i, card) in cascade.cards.iter().enumerate() {
+            let granted_by: Vec<String> = card
+                .granted_by
+                .iter()
+                .map(|(from, copies)| format!("{copies} from card {}", from + 1))
+                .collect();
+            println!(
+                "Card {}: {} matches, {} copies ({})",
+                i + 1,
+                card.matches,
+                card.copies,
+                if granted_by.is_empty() {
+                    "original only".to_string()
+                } else {
+                    granted_by.join(", ")
+                }
+            );
+        }
+    }
+
+    println!("Total cards: {}", cascade.total);
+    let mut granted: Vec<(usize, &C)> = cascade
+        .cards
+        .iter()
+        .map(|c| &c.granted)
+        .enumerate()
+        .collect();
+    granted.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(&b.0)));
+    println!("Top contributors:");
+    for (i, copies) in granted.into_iter().take(top) {
+        println!("  Card {}: granted {copies} copies", i + 1);
+    }
+    Ok(())
+}
+
+fn bench(args: &Args) -> Result<(), String> {
+    let default = DeckShape::default();
+    let shape = DeckShape {
+        cards: args.parsed("deck")?.unwrap_or(default.cards),
+        winning: args.parsed("winning")?.unwrap_or(default.winning),
+        ticket: args.parsed("ticket")?.unwrap_or(default.ticket),
+        max: args.parsed("max")?.unwrap_or(default.max),
+    };
+    let mut rng = Rng::new(args.parsed("seed")?.unwrap_or(0));
+    let rounds = args.parsed("rounds")?.unwrap_or(10);
+    let deck = random_deck(&mut rng, &shape)?;
+    let comparison = compare(&deck, rounds)?;
+
+    println!(
+        "{} cards, {} matches, {rounds} rounds",
+        comparison.cards, comparison.matches
+    );
+    println!("Bitset:   {:?}", comparison.bitset);
+    println!("Hash set: {:?}", comparison.hashset);
+    println!(
+        "Speedup:  {:.1}x",
+        comparison.hashset.as_secs_f64() / comparison.bitset.as_secs_f64()
+    );
+    Ok(())
+}
+
+fn compare_rules(args: &Args) -> Result<(), String> {
+    let rules: Vec<Rule> = match args.value("rules") {
+        Some(rules) => rules.split(',').map(str::parse).collect::<Result<_, _>>()?,
+        None => Rule::ALL.to_vec(),
+    };
+    let policy: OutOfRange = args.value("policy").unwrap_or("clamp").parse()?;
+    let cards = load_cards(args)?;
+    let matches: Vec<usize> = cards.iter().map(ScratchCard::matches).collect();
+
+    let width = rules.iter().map(|r| r.to_string().len()).max().unwrap_or(0);
+    for rule in rules {
+        match rule.score(&matches, policy) {
+            Ok(score) => println!("{:width$}  {score}", rule.to_string()),
+            Err(e) => println!("{:width$}  failed, {e}", rule.to_string()),
+        }
+    }
+    Ok(())
+}
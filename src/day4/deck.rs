@@ -0,0 +1,168 @@
+use std::fmt::Display;
+
+use super::ScratchCard;
+
+/// Problem with the card ids of a deck, lines start at 1.
+#[derive(Debug, PartialEq)]
+pub(super) enum IdIssue {
+    Duplicate {
+        id: usize,
+        first: usize,
+        line: usize,
+    },
+    /// Id below 1, cards are numbered from 1.
+    OutOfRange { id: usize, line: usize },
+    /// No card has the ids from `from` to `to`, both included.
+    Gap { from: usize, to: usize },
+    /// The card comes after a card with a greater id.
+    OutOfOrder {
+        id: usize,
+        previous: usize,
+        line: usize,
+    },
+}
+
+impl Display for IdIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdIssue::Duplicate { id, first, line } => {
+                write!(
+                    f,
+                    "card {id} on line {line} already appears on line {first}"
+                )
+            }
+            IdIssue::OutOfRange { id, line } => {
+                write!(
+                    f,
+                    "card {id} on line {line} is out of range, ids start at 1"
+                )
+            }
+            IdIssue::Gap { from, to } if from == to => write!(f, "card {from} is missing"),
+            IdIssue::Gap { from, to } => write!(f, "cards {from} to {to} are missing"),
+            IdIssue::OutOfOrder { id, previous, line } => {
+                write!(f, "card {id} on line {line} comes after card {previous}")
+            }
+        }
+    }
+}
+
+/// Issues of the deck, ids must be unique and go from 1 to the amount of
+/// cards. Order is only checked when `ordered` is set.
+pub(super) fn check_ids(cards: &[ScratchCard], ordered: bool) -> Vec<IdIssue> {
+    let mut issues = vec![];
+    if ordered {
+        for (line, pair) in cards.windows(2).enumerate() {
+            if pair[1].id < pair[0].id {
+                issues.push(IdIssue::OutOfOrder {
+                    id: pair[1].id,
+                    previous: pair[0].id,
+                    line: line + 2,
+                });
+            }
+        }
+    }
+
+    let mut lines: Vec<(usize, usize)> = cards
+        .iter()
+        .enumerate()
+        .map(|(line, card)| (card.id, line + 1))
+        .collect();
+    lines.sort_unstable();
+    let mut expected = 1;
+    for (i, &(id, line)) in lines.iter().enumerate() {
+        if id == 0 {
+            issues.push(IdIssue::OutOfRange { id, line });
+            continue;
+        }
+        if i > 0 && lines[i - 1].0 == id {
+            issues.push(IdIssue::Duplicate {
+                id,
+                first: lines[i - 1].1,
+                line,
+            });
+            continue;
+        }
+        if id > expected {
+            issues.push(IdIssue::Gap {
+                from: expected,
+                to: id - 1,
+            });
+        }
+        expected = id + 1;
+    }
+    issues
+}
+
+/// The cards sorted by id when `reorder` is set, or checked to already be.
+pub(super) fn arrange(
+    mut cards: Vec<ScratchCard>,
+    reorder: bool,
+) -> Result<Vec<ScratchCard>, Vec<IdIssue>> {
+    let issues = check_ids(&cards, !reorder);
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    cards.sort_by_key(|card| card.id);
+    Ok(cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deck(ids: &[usize]) -> Vec<ScratchCard> {
+        ids.iter()
+            .map(|id| format!("Card {id}: 1 2 | 2 3").parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_deck() {
+        assert!(check_ids(&deck(&[1, 2, 3]), true).is_empty());
+        assert!(check_ids(&deck(&[]), true).is_empty());
+    }
+
+    #[test]
+    fn test_issues() {
+        assert_eq!(
+            check_ids(&deck(&[2, 5, 4, 5]), true),
+            [
+                IdIssue::OutOfOrder {
+                    id: 4,
+                    previous: 5,
+                    line: 3
+                },
+                IdIssue::Gap { from: 1, to: 1 },
+                IdIssue::Gap { from: 3, to: 3 },
+                IdIssue::Duplicate {
+                    id: 5,
+                    first: 2,
+                    line: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_id_zero() {
+        assert_eq!(
+            check_ids(&deck(&[0, 1]), true),
+            [IdIssue::OutOfRange { id: 0, line: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_arrange() {
+        let ids = |cards: Vec<ScratchCard>| cards.iter().map(|c| c.id).collect::<Vec<_>>();
+        assert_eq!(ids(arrange(deck(&[3, 1, 2]), true).unwrap()), [1, 2, 3]);
+        assert_eq!(
+            arrange(deck(&[3, 1, 2]), false).unwrap_err(),
+            [IdIssue::OutOfOrder {
+                id: 1,
+                previous: 3,
+                line: 2
+            }]
+        );
+        assert!(arrange(deck(&[1, 1]), true).is_err());
+    }
+}
@@ -1,17 +1,65 @@
-use std::{collections::HashSet, str::FromStr};
+use std::{fmt::Display, str::FromStr};
 
+mod bench;
+mod cascade;
+pub(crate) mod cli;
+mod deck;
+mod numbers;
+mod scoring;
+
+use cascade::OutOfRange;
+use numbers::NumberSet;
+use scoring::Rule;
+
+#[derive(Debug)]
 struct ScratchCard {
-    _id: usize,
-    winning_numbers: HashSet<usize>,
-    ticket_numbers: HashSet<usize>,
+    id: usize,
+    winning_numbers: NumberSet,
+    ticket_numbers: NumberSet,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 enum ScratchCardParseError {
     InvalidFormat,
     IdNotNumeric,
     InvalidWinningNumbers,
     InvalidTicketNumbers,
+    DuplicateWinningNumber(usize),
+    DuplicateTicketNumber(usize),
+}
+
+impl Display for ScratchCardParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScratchCardParseError::InvalidFormat => write!(f, "invalid format"),
+            ScratchCardParseError::IdNotNumeric => write!(f, "id is not a number"),
+            ScratchCardParseError::InvalidWinningNumbers => write!(f, "invalid winning numbers"),
+            ScratchCardParseError::InvalidTicketNumbers => write!(f, "invalid ticket numbers"),
+            ScratchCardParseError::DuplicateWinningNumber(n) => {
+                write!(f, "winning number {n} is listed twice")
+            }
+            ScratchCardParseError::DuplicateTicketNumber(n) => {
+                write!(f, "ticket number {n} is listed twice")
+            }
+        }
+    }
+}
+
+/// Parses a list of numbers, rejecting the duplicates a set would hide.
+fn parse_numbers(
+    list: &str,
+    invalid: ScratchCardParseError,
+    duplicate: fn(usize) -> ScratchCardParseError,
+) -> Result<NumberSet, ScratchCardParseError> {
+    let mut numbers = NumberSet::default();
+    for n in list.split_ascii_whitespace() {
+        match n.parse() {
+            Ok(n) if !numbers.insert(n) => return Err(duplicate(n)),
+            Ok(_) => {}
+            Err(_) => return Err(invalid),
+        }
+    }
+    Ok(numbers)
 }
 
 impl FromStr for ScratchCard {
@@ -32,41 +80,54 @@ impl FromStr for ScratchCard {
             .split_once(" | ")
             .ok_or(ScratchCardParseError::InvalidFormat)?;
 
-        let winning_numbers: HashSet<usize> = winning_numbers
-            .split_ascii_whitespace()
-            .map(|e| e.parse::<usize>())
-            .collect::<Result<_, _>>()
-            .map_err(|_| ScratchCardParseError::InvalidWinningNumbers)?;
-
-        let ticket_numbers: HashSet<usize> = ticket_numbers
-            .split_ascii_whitespace()
-            .map(|e| e.parse::<usize>())
-            .collect::<Result<_, _>>()
-            .map_err(|_| ScratchCardParseError::InvalidTicketNumbers)?;
+        let winning_numbers = parse_numbers(
+            winning_numbers,
+            ScratchCardParseError::InvalidWinningNumbers,
+            ScratchCardParseError::DuplicateWinningNumber,
+        )?;
+        let ticket_numbers = parse_numbers(
+            ticket_numbers,
+            ScratchCardParseError::InvalidTicketNumbers,
+            ScratchCardParseError::DuplicateTicketNumber,
+        )?;
 
         Ok(ScratchCard {
-            _id: id,
+            id,
             winning_numbers,
             ticket_numbers,
         })
     }
 }
 
+impl ScratchCard {
+    /// Amount of ticket numbers that are winning numbers.
+    fn matches(&self) -> usize {
+        self.winning_numbers.intersection_len(&self.ticket_numbers)
+    }
+}
+
+/// Match count of every card, in the order of the ids.
+fn deck_matches(input: &str) -> Vec<usize> {
+    let cards = input
+        .lines()
+        .map(|l| l.parse::<ScratchCard>().expect("invalid input"))
+        .collect();
+    deck::arrange(cards, false)
+        .unwrap_or_else(|issues| panic!("Invalid deck: {}", issues[0]))
+        .iter()
+        .map(ScratchCard::matches)
+        .collect()
+}
+
 pub(crate) mod part1 {
     use super::*;
 
     pub fn resolve(input: &str) -> String {
-        input
-            .lines()
-            .map(|l| l.parse::<ScratchCard>().expect("invalid input"))
-            .map(|s| {
-                s.winning_numbers
-                    .intersection(&s.ticket_numbers)
-                    .fold(0usize, |acc, _| acc + 1)
-            })
-            .filter(|&len| len > 0)
-            .map(|len| 2usize.pow((len - 1) as u32))
-            .sum::<usize>()
+        let matches = deck_matches(input);
+
+        Rule::Doubling
+            .score(&matches, OutOfRange::Error)
+            .unwrap_or_else(|e| panic!("Invalid deck: {e}"))
             .to_string()
     }
 
@@ -89,27 +150,12 @@ pub(crate) mod part2 {
     use super::*;
 
     pub fn resolve(input: &str) -> String {
-        let lines: Vec<_> = input.lines().collect();
-        let mut tickets = vec![1; lines.len()];
-
-        input
-            .lines()
-            .map(|l| l.parse::<ScratchCard>().expect("invalid input"))
-            .map(|s| {
-                s.winning_numbers
-                    .intersection(&s.ticket_numbers)
-                    .fold(0usize, |acc, _| acc + 1)
-            })
-            .enumerate()
-            .for_each(|(i, winning_times)| {
-                if winning_times != 0 {
-                    for next in i + 1..=i + winning_times {
-                        tickets[next] += tickets[i];
-                    }
-                }
-            });
-
-        tickets.iter().sum::<usize>().to_string()
+        let matches = deck_matches(input);
+
+        Rule::Cascade
+            .score(&matches, OutOfRange::Error)
+            .unwrap_or_else(|e| panic!("Invalid deck: {e}"))
+            .to_string()
     }
 
     #[cfg(test)]
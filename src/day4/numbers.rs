@@ -0,0 +1,80 @@
+use std::collections::BTreeSet;
+
+/// Numbers below this bound are stored in the bitset, the others in the
+/// fallback set.
+const SMALL_BOUND: usize = 128;
+
+/// Set of card numbers, a fixed-width bitset for the small numbers the
+/// puzzle uses with a sorted fallback for the large ones.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct NumberSet {
+    small: [u64; SMALL_BOUND / 64],
+    large: BTreeSet<usize>,
+}
+
+impl NumberSet {
+    /// Adds the number, returns whether it was not in the set yet.
+    pub fn insert(&mut self, n: usize) -> bool {
+        if n >= SMALL_BOUND {
+            return self.large.insert(n);
+        }
+        let (word, bit) = (n / 64, 1 << (n % 64));
+        let added = self.small[word] & bit == 0;
+        self.small[word] |= bit;
+        added
+    }
+
+    /// Amount of numbers in both sets.
+    pub fn intersection_len(&self, other: &NumberSet) -> usize {
+        let small: u32 = self
+            .small
+            .iter()
+            .zip(other.small.iter())
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        let (fewer, more) = if self.large.len() <= other.large.len() {
+            (&self.large, &other.large)
+        } else {
+            (&other.large, &self.large)
+        };
+        small as usize + fewer.iter().filter(|n| more.contains(n)).count()
+    }
+}
+
+impl FromIterator<usize> for NumberSet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut set = NumberSet::default();
+        for n in iter {
+            set.insert(n);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut set = NumberSet::default();
+        assert!(set.insert(0));
+        assert!(set.insert(63));
+        assert!(set.insert(64));
+        assert!(set.insert(127));
+        assert!(set.insert(128));
+        assert!(set.insert(1_000_000));
+        assert!(!set.insert(64));
+        assert!(!set.insert(1_000_000));
+        assert_eq!(set.intersection_len(&set), 6);
+    }
+
+    #[test]
+    fn test_intersection_len() {
+        let a: NumberSet = [1, 48, 83, 86, 17, 200, 5000].into_iter().collect();
+        let b: NumberSet = [83, 86, 6, 31, 17, 9, 48, 53, 5000].into_iter().collect();
+        assert_eq!(a.intersection_len(&b), 5);
+        assert_eq!(b.intersection_len(&a), 5);
+        assert_eq!(a.intersection_len(&NumberSet::default()), 0);
+    }
+}
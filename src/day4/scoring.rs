@@ -0,0 +1,151 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::cascade::{Cascade, CascadeError, OutOfRange};
+
+/// How a deck is scored from the match count of its cards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Rule {
+    /// Points doubling with every match after the first, as in part one.
+    Doubling,
+    /// One point per match.
+    Linear,
+    /// Total of cards after every card wins copies of the next ones, as in
+    /// part two.
+    Cascade,
+    /// Same as [`Rule::Cascade`], but a card never has more copies than the
+    /// cap.
+    CappedCascade(usize),
+    /// Cards win copies of the cards before them instead, the last card
+    /// being played first.
+    Backward,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 5] = [
+        Rule::Doubling,
+        Rule::Linear,
+        Rule::Cascade,
+        Rule::CappedCascade(10),
+        Rule::Backward,
+    ];
+
+    /// Score of the deck, `policy` applies to the copies won past either end
+    /// of the deck.
+    pub fn score(&self, matches: &[usize], policy: OutOfRange) -> Result<usize, CascadeError> {
+        match self {
+            Rule::Doubling => matches
+                .iter()
+                .enumerate()
+                .filter(|(_, &matches)| matches > 0)
+                .try_fold(0usize, |score, (card, &matches)| {
+                    1usize
+                        .checked_shl((matches - 1) as u32)
+                        .filter(|_| matches <= usize::BITS as usize)
+                        .and_then(|points| score.checked_add(points))
+                        .ok_or(CascadeError::Overflow { card })
+                }),
+            Rule::Linear => Ok(matches.iter().sum()),
+            Rule::Cascade => Ok(Cascade::<usize>::simulate(matches, policy)?.total),
+            Rule::CappedCascade(cap) => {
+                Ok(Cascade::<usize>::simulate_capped(matches, policy, Some(cap))?.total)
+            }
+            Rule::Backward => {
+                let reversed: Vec<usize> = matches.iter().rev().copied().collect();
+                Cascade::<usize>::simulate(&reversed, policy)
+                    .map(|cascade| cascade.total)
+                    .map_err(|e| match e {
+                        // Report the card in the original order
+                        CascadeError::OutOfRange { card, matches } => CascadeError::OutOfRange {
+                            card: reversed.len() - 1 - card,
+                            matches,
+                        },
+                        CascadeError::Overflow { card } => CascadeError::Overflow {
+                            card: reversed.len() - 1 - card,
+                        },
+                        CascadeError::TotalOverflow => CascadeError::TotalOverflow,
+                    })
+            }
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doubling" => Ok(Rule::Doubling),
+            "linear" => Ok(Rule::Linear),
+            "cascade" => Ok(Rule::Cascade),
+            "backward" => Ok(Rule::Backward),
+            _ => match s.strip_prefix("capped:").map(str::parse) {
+                Some(Ok(cap)) => Ok(Rule::CappedCascade(cap)),
+                _ => Err(format!(
+                    "Unknown rule `{s}`, expected doubling, linear, cascade, capped:N or backward"
+                )),
+            },
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rule::Doubling => write!(f, "doubling"),
+            Rule::Linear => write!(f, "linear"),
+            Rule::Cascade => write!(f, "cascade"),
+            Rule::CappedCascade(cap) => write!(f, "capped:{cap}"),
+            Rule::Backward => write!(f, "backward"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [usize; 6] = [4, 2, 2, 1, 0, 0];
+
+    #[test]
+    fn test_puzzle_rules() {
+        assert_eq!(Rule::Doubling.score(&EXAMPLE, OutOfRange::Error), Ok(13));
+        assert_eq!(Rule::Cascade.score(&EXAMPLE, OutOfRange::Error), Ok(30));
+    }
+
+    #[test]
+    fn test_alternative_rules() {
+        assert_eq!(Rule::Linear.score(&EXAMPLE, OutOfRange::Error), Ok(9));
+        // Copies per card: 1, 2, 4, 4, 4, 1
+        assert_eq!(
+            Rule::CappedCascade(4).score(&EXAMPLE, OutOfRange::Error),
+            Ok(16)
+        );
+        // Card 2 looks past the start of the deck before card 1 is played
+        assert_eq!(
+            Rule::Backward.score(&EXAMPLE, OutOfRange::Error),
+            Err(CascadeError::OutOfRange {
+                card: 1,
+                matches: 2
+            })
+        );
+        // Copies per card, last to first: 1, 1, 1, 2, 3, 6
+        assert_eq!(Rule::Backward.score(&EXAMPLE, OutOfRange::Clamp), Ok(14));
+    }
+
+    #[test]
+    fn test_doubling_overflow() {
+        assert_eq!(
+            Rule::Doubling.score(&[1, 65], OutOfRange::Clamp),
+            Err(CascadeError::Overflow { card: 1 })
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        for rule in Rule::ALL {
+            assert_eq!(rule.to_string().parse::<Rule>(), Ok(rule));
+        }
+        assert!("capped:x".parse::<Rule>().is_err());
+        assert!("tripling".parse::<Rule>().is_err());
+    }
+}
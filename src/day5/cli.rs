@@ -0,0 +1,140 @@
+use super::{reference::fuzz, validate::validate, *};
+use crate::{cli::Args, rng::Rng};
+
+const USAGE: &str = "Usage: day5 <command> [--input FILE]
+Commands:
+  check
+      Lists malformed rows, empty, overflowing and overlapping ranges, and
+      an odd amount of seeds, empty or overflowing seed ranges for part two.
+  categories
+      Lists the categories of the almanac, in the order of the chain.
+  compose [--from CATEGORY] [--to CATEGORY] [--values A..B]
+      Prints the map made of every category map between the two
+      categories, seed to location by default, or the images of the given
+      values and the lowest of them.
+  trace (--seed N | --location N | --locations A..B)
+      Prints the value of every category for the seed, or for each seed
+      leading to the location. With a range of locations, prints the
+      ranges of seeds leading to it.
+  fuzz [--count N] [--seed S] [--max N]
+      Compares both solvers with a brute-force reference on N random
+      almanacs (1000 by default) with values below max (100 by default),
+      printing the first almanac they disagree on.";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(
+        args,
+        &[],
+        &[
+            "input",
+            "values",
+            "from",
+            "to",
+            "seed",
+            "location",
+            "locations",
+            "count",
+            "max",
+        ],
+    )?;
+    match args.positional() {
+        [command] if command == "check" => check(&args),
+        [command] if command == "categories" => categories(&args),
+        [command] if command == "compose" => compose(&args),
+        [command] if command == "trace" => trace(&args),
+        [command] if command == "fuzz" => run_fuzz(&args),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn check(args: &Args) -> Result<(), String> {
+    let issues = validate(&args.input(5)?);
+    if issues.is_empty() {
+        println!("No issues");
+    }
+    for issue in issues {
+        println!("{issue}");
+    }
+    Ok(())
+}
+
+fn categories(args: &Args) -> Result<(), String> {
+    let almanac: Almanac = args.input(5)?.parse()?;
+    println!("{}", almanac.categories().join(" -> "));
+    Ok(())
+}
+
+fn compose(args: &Args) -> Result<(), String> {
+    let almanac: Almanac = args.input(5)?.parse()?;
+    let categories = almanac.categories();
+    let from = args.value("from").unwrap_or(categories[0]);
+    let to = args.value("to").unwrap_or(categories[categories.len() - 1]);
+    let map = almanac.between(from, to)?;
+    let Some(values) = args.range("values")? else {
+        println!("{from} to {to}:");
+        print!("{map}");
+        return Ok(());
+    };
+
+    let values = *values.start()..values.end().saturating_add(1);
+    let images = map.map_range(values);
+    for image in images.iter() {
+        println!("{}..{}", image.start, image.end);
+    }
+    if let Some(lowest) = images.iter().map(|i| i.start).min() {
+        println!("Lowest {to}: {lowest}");
+    }
+    Ok(())
+}
+
+fn print_chain(almanac: &Almanac, seed: usize) {
+    let steps: Vec<String> = almanac
+        .categories()
+        .iter()
+        .zip(almanac.chain(seed))
+        .map(|(category, value)| format!("{category} {value}"))
+        .collect();
+    println!("{}", steps.join(" -> "));
+}
+
+fn trace(args: &Args) -> Result<(), String> {
+    let almanac: Almanac = args.input(5)?.parse()?;
+    let categories = almanac.categories();
+    let (first, last) = (categories[0], categories[categories.len() - 1]);
+
+    if let Some(seed) = args.parsed("seed")? {
+        print_chain(&almanac, seed);
+    } else if let Some(location) = args.parsed("location")? {
+        let seeds = almanac.composed().preimage(location);
+        if seeds.is_empty() {
+            println!("No {first} leads to {last} {location}");
+        }
+        for seed in seeds {
+            print_chain(&almanac, seed);
+        }
+    } else if let Some(locations) = args.range("locations")? {
+        let locations = *locations.start()..locations.end().saturating_add(1);
+        let seeds = almanac.composed().preimage_range(locations);
+        if seeds.is_empty() {
+            println!("No {first} leads to these {last} values");
+        }
+        for seeds in seeds {
+            println!("{first} {}..{}", seeds.start, seeds.end);
+        }
+    } else {
+        return Err(USAGE.to_string());
+    }
+    Ok(())
+}
+
+fn run_fuzz(args: &Args) -> Result<(), String> {
+    let count = args.parsed("count")?.unwrap_or(1000);
+    let max: usize = args.parsed("max")?.unwrap_or(100);
+    if max == 0 {
+        return Err("--max must be positive".to_string());
+    }
+    let mut rng = Rng::new(args.parsed("seed")?.unwrap_or(0));
+    fuzz(&mut rng, count, max)?;
+    println!("Solvers agree with the reference on {count} almanacs");
+    Ok(())
+}
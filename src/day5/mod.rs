@@ -1,97 +1,187 @@
-use std::{fmt::Debug, ops::Range, str::FromStr};
+use std::{ops::Range, str::FromStr};
 
+use crate::range_set::RangeSet;
+
+pub(crate) mod cli;
+mod piecewise;
+mod reference;
+mod validate;
+
+use piecewise::PiecewiseMap;
+
+/// Section of the almanac converting one category to the next.
 #[derive(Debug)]
-struct Almanac {
-    seeds: Vec<usize>,
-    category_conversion: Vec<Vec<(Range<usize>, Range<usize>)>>,
+struct CategoryMap {
+    source: String,
+    destination: String,
+    conversions: Vec<(Range<usize>, Range<usize>)>,
 }
 
 #[derive(Debug)]
-struct IntersectRange<T> {
-    parallel: T,
-    remainder_self: Vec<T>,
+struct Almanac {
+    seeds: Vec<usize>,
+    /// Maps in the order of the chain, each one starting from the category
+    /// the previous one ends with.
+    maps: Vec<CategoryMap>,
 }
 
-trait Intersect<A = Self>: Sized {
-    fn intersect(&self, other: &Self) -> Option<IntersectRange<Self>>;
-}
+impl Almanac {
+    /// Categories in the order of the chain, `seed` first.
+    fn categories(&self) -> Vec<&str> {
+        let mut categories = vec!["seed"];
+        categories.extend(self.maps.iter().map(|m| m.destination.as_str()));
+        categories
+    }
 
-impl<T: Ord + Copy + Debug> Intersect<T> for Range<T> {
-    fn intersect(&self, other: &Self) -> Option<IntersectRange<Self>> {
-        // If not intersect
-        if !(self.start <= other.end && other.start <= self.end) {
-            return None;
+    /// The category maps from `from` to `to` composed into a single map.
+    fn between(&self, from: &str, to: &str) -> Result<PiecewiseMap, String> {
+        let categories = self.categories();
+        let position = |category| {
+            categories
+                .iter()
+                .position(|&c| c == category)
+                .ok_or(format!("Unknown category `{category}`"))
+        };
+        let (start, end) = (position(from)?, position(to)?);
+        if start > end {
+            return Err(format!(
+                "No conversion from {from} to {to}, {to} comes first"
+            ));
         }
-        // Find the intersection
-        let start = std::cmp::max(other.start, self.start);
-        let end = std::cmp::min(other.end, self.end);
-        let parallel = start..end;
-
-        // Collect the remainder of the self range
-        let mut remainder_self = vec![];
-        if self.start < parallel.start {
-            remainder_self.push(self.start..parallel.start);
+        Ok(self.maps[start..end]
+            .iter()
+            .fold(PiecewiseMap::identity(), |map, category| {
+                map.then(&PiecewiseMap::from_conversions(&category.conversions))
+            }))
+    }
+
+    /// Value of every category of the chain for the seed.
+    fn chain(&self, seed: usize) -> Vec<usize> {
+        let mut values = vec![seed];
+        for category in self.maps.iter() {
+            let map = PiecewiseMap::from_conversions(&category.conversions);
+            values.push(map.apply(values[values.len() - 1]));
         }
-        if self.end > parallel.end {
-            remainder_self.push(parallel.end..self.end);
+        values
+    }
+
+    /// Seed ranges of part two, read from the start and length pairs.
+    fn seed_ranges(&self) -> Result<Vec<Range<usize>>, String> {
+        if self.seeds.len() % 2 == 1 {
+            return Err(format!(
+                "{} seeds cannot be read as pairs",
+                self.seeds.len()
+            ));
         }
+        self.seeds
+            .chunks_exact(2)
+            .map(|pair| match pair[0].checked_add(pair[1]) {
+                Some(end) => Ok(pair[0]..end),
+                None => Err(format!(
+                    "Seed range {} of length {} ends past usize",
+                    pair[0], pair[1]
+                )),
+            })
+            .collect()
+    }
+
+    /// Every category map composed into a single seed to location map.
+    fn composed(&self) -> PiecewiseMap {
+        let categories = self.categories();
+        self.between(categories[0], categories[categories.len() - 1])
+            .expect("Categories of the chain are known")
+    }
+}
 
-        Some(IntersectRange {
-            parallel,
-            remainder_self,
+/// Parses a section header, `seed-to-soil map:` gives `(seed, soil)`.
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    header
+        .strip_suffix(" map:")
+        .and_then(|h| h.split_once("-to-"))
+        .map(|(source, destination)| (source.to_string(), destination.to_string()))
+        .ok_or(format!("Invalid map header `{header}`"))
+}
+
+/// Seeds of the first line, `seeds: ` followed by numbers.
+fn parse_seeds(line: &str) -> Result<Vec<usize>, String> {
+    line.strip_prefix("seeds: ")
+        .ok_or("expected `seeds: <numbers>`".to_string())?
+        .split_whitespace()
+        .map(|n| n.parse().map_err(|_| format!("invalid seed `{n}`")))
+        .collect()
+}
+
+/// Numbers of a map row, destination start, source start and length.
+fn parse_row(row: &str) -> Result<[usize; 3], String> {
+    let numbers = row
+        .split_whitespace()
+        .map(|n| {
+            n.parse::<usize>()
+                .map_err(|_| format!("invalid number `{n}`"))
         })
+        .collect::<Result<Vec<_>, _>>()?;
+    numbers
+        .try_into()
+        .map_err(|numbers: Vec<usize>| format!("expected 3 numbers, found {}", numbers.len()))
+}
+
+/// Source and destination ranges of the row, `None` when an end does not
+/// fit in a `usize`.
+fn conversion([destination, source, length]: [usize; 3]) -> Option<(Range<usize>, Range<usize>)> {
+    Some((
+        source..source.checked_add(length)?,
+        destination..destination.checked_add(length)?,
+    ))
+}
+
+/// Non-empty sections of the input after the seeds, as their lines with
+/// the line number.
+fn sections(s: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut sections = vec![vec![]];
+    for (i, line) in s.lines().enumerate().skip(1) {
+        if line.is_empty() {
+            sections.push(vec![]);
+        } else {
+            sections.last_mut().unwrap().push((i + 1, line));
+        }
     }
+    sections.retain(|s| !s.is_empty());
+    sections
 }
 
 impl FromStr for Almanac {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (seeds, remain) = s.split_once('\n').ok_or("Invalid Format")?;
-        let (_prefix, seeds) = seeds.split_once(": ").ok_or("Invalid seed")?;
-
-        let make_range_conversion = |s: &str| {
-            let numbers: Vec<usize> = s
-                .split_whitespace()
-                .map(|s| s.parse::<usize>().expect("Invalid number in category"))
-                .collect();
-            let &[dest_start, source_start, length] = &numbers[..3] else {
-                unreachable!()
-            };
-            (
-                (source_start..source_start + length),
-                dest_start..dest_start + length,
-            )
-        };
+        let seeds = s.lines().next().ok_or("Invalid Format")?;
+        let seeds = parse_seeds(seeds).map_err(|e| format!("Line 1: {e}"))?;
 
-        let seeds = seeds
-            .split_ascii_whitespace()
-            .map(|e| e.parse::<usize>())
-            .collect::<Result<_, _>>()
-            .map_err(|_| "Invalid seed")?;
-        let category_conversion = remain
-            .lines()
-            .fold(vec![], |mut acc, l| {
-                if l.is_empty() {
-                    acc.push(vec![]);
-                } else {
-                    acc.last_mut().unwrap().push(l);
-                }
-                acc
-            })
-            .iter()
-            .map(|c| {
-                c.iter()
-                    .skip(1)
-                    .map(|&c| make_range_conversion(c))
-                    .collect()
-            })
-            .collect();
+        let mut maps: Vec<CategoryMap> = vec![];
+        for section in sections(s) {
+            let (source, destination) = parse_header(section[0].1)?;
+            let expected = maps.last().map_or("seed", |m| m.destination.as_str());
+            if source != expected {
+                return Err(format!(
+                    "Map from {source} to {destination} does not follow the {expected} category"
+                ));
+            }
+            if source == destination || maps.iter().any(|m| m.source == destination) {
+                return Err(format!("Category {destination} appears twice in the chain"));
+            }
+            maps.push(CategoryMap {
+                source,
+                destination,
+                conversions: section[1..]
+                    .iter()
+                    .map(|&(line, row)| {
+                        let numbers = parse_row(row).map_err(|e| format!("Line {line}: {e}"))?;
+                        conversion(numbers).ok_or(format!("Line {line}: range end overflows"))
+                    })
+                    .collect::<Result<_, _>>()?,
+            });
+        }
 
-        Ok(Almanac {
-            seeds,
-            category_conversion,
-        })
+        Ok(Almanac { seeds, maps })
     }
 }
 
@@ -100,22 +190,17 @@ pub(crate) mod part1 {
 
     pub fn resolve(input: &str) -> String {
         let almanac: Almanac = input.parse().expect("Invalid input");
+        lowest_location(&almanac).unwrap().to_string()
+    }
 
-        let mut last_category = Vec::with_capacity(almanac.seeds.len());
-        for &seed in &almanac.seeds {
-            let mut previous_category = seed;
-            for category in &almanac.category_conversion {
-                for conv in category {
-                    if conv.0.contains(&previous_category) {
-                        previous_category = conv.1.start + (previous_category - conv.0.start);
-                        break;
-                    }
-                }
-            }
-
-            last_category.push(previous_category);
-        }
-        last_category.iter().min().unwrap().to_string()
+    /// Lowest location of the seeds, `None` without any seed.
+    pub(super) fn lowest_location(almanac: &Almanac) -> Option<usize> {
+        let seed_to_location = almanac.composed();
+        almanac
+            .seeds
+            .iter()
+            .map(|&seed| seed_to_location.apply(seed))
+            .min()
     }
 
     #[cfg(test)]
@@ -150,44 +235,31 @@ pub(crate) mod part2 {
 
     pub fn resolve(input: &str) -> String {
         let almanac: Almanac = input.parse().expect("Invalid input");
-        let true_seeds: Vec<_> = almanac
-            .seeds
-            .chunks(2)
-            .map(|e| (e[0]..e[0] + e[1]))
-            .collect();
-        let mut last_category = vec![];
-        let mut previous_category = true_seeds;
-        for category in almanac.category_conversion {
-            let mut next_category = vec![];
-            for conversion in category {
-                let mut remainder = vec![];
-
-                for prev_cat in previous_category {
-                    match prev_cat.intersect(&conversion.0) {
-                        Some(IntersectRange {
-                            parallel,
-                            remainder_self,
-                        }) => {
-                            next_category.push(translate_category(&parallel, &conversion));
-                            remainder.extend(remainder_self);
-                        }
-                        None => {
-                            remainder.push(prev_cat);
-                        }
-                    }
+        lowest_location(&almanac)
+            .and_then(|location| location.ok_or("every seed range is empty".to_string()))
+            .unwrap_or_else(|e| panic!("Invalid seeds: {e}"))
+            .to_string()
+    }
+
+    /// Lowest location of the seed ranges, `None` when they are all empty.
+    pub(super) fn lowest_location(almanac: &Almanac) -> Result<Option<usize>, String> {
+        let mut current: RangeSet<usize> = almanac.seed_ranges()?.into_iter().collect();
+        for category in almanac.maps.iter() {
+            let mut next = RangeSet::new();
+            // Values already taken by a conversion, the first one wins
+            let mut claimed = RangeSet::new();
+            for conversion in category.conversions.iter() {
+                let source = RangeSet::from(conversion.0.clone());
+                let moved = current.intersection(&source).difference(&claimed);
+                for range in moved.ranges() {
+                    next.insert(translate_category(range, conversion));
                 }
-                previous_category = remainder;
+                claimed = claimed.union(&source);
             }
-            next_category.extend(previous_category.clone());
-            previous_category = next_category;
-            last_category = previous_category.clone();
+            let unmapped = current.intersection(&claimed.complement(0..usize::MAX));
+            current = next.union(&unmapped);
         }
-        last_category
-            .into_iter()
-            .map(|r| r.start)
-            .min()
-            .unwrap()
-            .to_string()
+        Ok(current.first())
     }
 
     #[cfg(test)]
@@ -204,3 +276,100 @@ pub(crate) mod part2 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn example() -> Almanac {
+        fs::read_to_string("input/day5/example")
+            .expect("Need example file to test")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_categories() {
+        let almanac = example();
+        assert_eq!(
+            almanac.categories(),
+            [
+                "seed",
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity",
+                "location"
+            ]
+        );
+        assert_eq!(almanac.maps[3].source, "water");
+    }
+
+    #[test]
+    fn test_between() {
+        let almanac = example();
+        // Seed 79 has soil 81, fertilizer 81, water 81, light 74,
+        // temperature 78, humidity 78 and location 82
+        let soil_to_humidity = almanac.between("soil", "humidity").unwrap();
+        assert_eq!(soil_to_humidity.apply(81), 78);
+        assert_eq!(almanac.between("light", "light").unwrap().apply(74), 74);
+        assert_eq!(almanac.composed().apply(79), 82);
+        assert!(almanac.between("humidity", "soil").is_err());
+        assert!(almanac.between("seed", "sunlight").is_err());
+    }
+
+    #[test]
+    fn test_seed_ranges() {
+        assert_eq!(example().seed_ranges(), Ok(vec![79..93, 55..68]));
+        let odd: Almanac = "seeds: 1 2 3".parse().unwrap();
+        assert_eq!(
+            part2::lowest_location(&odd),
+            Err("3 seeds cannot be read as pairs".to_string())
+        );
+        let overflow: Almanac = format!("seeds: {} 1", usize::MAX).parse().unwrap();
+        assert_eq!(
+            overflow.seed_ranges(),
+            Err(format!(
+                "Seed range {} of length 1 ends past usize",
+                usize::MAX
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_seeds() {
+        assert_eq!(parse_seeds("seeds: 79 14"), Ok(vec![79, 14]));
+        assert_eq!(parse_seeds("seeds: "), Ok(vec![]));
+        assert!(parse_seeds("seeds:79 14").is_err());
+        assert!(parse_seeds("plants: 79 14").is_err());
+        assert_eq!(
+            "seeds: 1 x".parse::<Almanac>().unwrap_err(),
+            "Line 1: invalid seed `x`"
+        );
+    }
+
+    #[test]
+    fn test_chain_and_back() {
+        let almanac = example();
+        assert_eq!(almanac.chain(79), [79, 81, 81, 81, 74, 78, 78, 82]);
+        assert_eq!(almanac.composed().preimage(82), [79]);
+        assert_eq!(
+            almanac.composed().preimage_range(40..47),
+            [14..15, 19..22, 52..54, 82..83]
+        );
+    }
+
+    #[test]
+    fn test_broken_chain() {
+        let input = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nwater-to-light map:\n1 2 3\n";
+        assert!(input.parse::<Almanac>().is_err());
+        let input = "seeds: 1 2\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-seed map:\n1 2 3\n";
+        assert!(input.parse::<Almanac>().is_err());
+        assert!("seeds: 1 2\n\nseed to soil:\n1 2 3\n"
+            .parse::<Almanac>()
+            .is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use std::{fmt::Display, ops::Range};
+
+/// Values from `start` up to the start of the next piece are shifted by
+/// `offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Piece {
+    start: usize,
+    offset: i128,
+}
+
+/// Map over every `usize` made of sorted pieces, the first one starting at
+/// 0 and the last one going up to `usize::MAX`. Adjacent pieces never have
+/// the same offset.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
+
+fn shift(value: usize, offset: i128) -> usize {
+    (value as i128 + offset) as usize
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        PiecewiseMap {
+            pieces: vec![Piece {
+                start: 0,
+                offset: 0,
+            }],
+        }
+    }
+
+    /// Map of an almanac section, values outside of every source range are
+    /// unchanged and the first range containing a value wins, as in part
+    /// one.
+    pub fn from_conversions(conversions: &[(Range<usize>, Range<usize>)]) -> Self {
+        let mut map = PiecewiseMap::identity();
+        for (source, destination) in conversions.iter().rev() {
+            map.overlay(source, destination.start as i128 - source.start as i128);
+        }
+        map
+    }
+
+    /// Index of the piece containing the value.
+    fn piece(&self, value: usize) -> usize {
+        self.pieces.partition_point(|p| p.start <= value) - 1
+    }
+
+    /// Exclusive end of the piece, `None` for the last one.
+    fn end(&self, piece: usize) -> Option<usize> {
+        self.pieces.get(piece + 1).map(|p| p.start)
+    }
+
+    /// Shifts the whole range by `offset`, whatever it was mapped to.
+    fn overlay(&mut self, range: &Range<usize>, offset: i128) {
+        if range.is_empty() {
+            return;
+        }
+        let after = self.pieces[self.piece(range.end)].offset;
+        self.pieces
+            .retain(|p| !(range.start..=range.end).contains(&p.start));
+        let i = self.pieces.partition_point(|p| p.start < range.start);
+        self.pieces.insert(
+            i,
+            Piece {
+                start: range.start,
+                offset,
+            },
+        );
+        self.pieces.insert(
+            i + 1,
+            Piece {
+                start: range.end,
+                offset: after,
+            },
+        );
+        self.merge();
+    }
+
+    fn merge(&mut self) {
+        self.pieces
+            .dedup_by(|next, previous| next.offset == previous.offset);
+    }
+
+    pub fn apply(&self, value: usize) -> usize {
+        shift(value, self.pieces[self.piece(value)].offset)
+    }
+
+    /// The map applying `self` then `next`.
+    pub fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let mut pieces = vec![];
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let image_start = shift(piece.start, piece.offset);
+            let image_end = self.end(i).map(|end| shift(end, piece.offset));
+            let first = next.piece(image_start);
+            pieces.push(Piece {
+                start: piece.start,
+                offset: piece.offset + next.pieces[first].offset,
+            });
+            for split in next.pieces[first + 1..]
+                .iter()
+                .take_while(|p| image_end.is_none_or(|end| p.start < end))
+            {
+                pieces.push(Piece {
+                    start: shift(split.start, -piece.offset),
+                    offset: piece.offset + split.offset,
+                });
+            }
+        }
+        let mut map = PiecewiseMap { pieces };
+        map.merge();
+        map
+    }
+
+    /// Images of the range, one per piece it overlaps, found with a single
+    /// binary search.
+    pub fn map_range(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        if range.is_empty() {
+            return vec![];
+        }
+        let mut images = vec![];
+        let mut i = self.piece(range.start);
+        let mut start = range.start;
+        while start < range.end {
+            let end = self.end(i).map_or(range.end, |end| end.min(range.end));
+            let offset = self.pieces[i].offset;
+            images.push(shift(start, offset)..shift(end, offset));
+            start = end;
+            i += 1;
+        }
+        images
+    }
+}
+
+impl PiecewiseMap {
+    /// Domain of the piece as `i128`, the end being exclusive.
+    fn domain(&self, piece: usize) -> Range<i128> {
+        let end = self
+            .end(piece)
+            .map_or(usize::MAX as i128 + 1, |end| end as i128);
+        self.pieces[piece].start as i128..end
+    }
+
+    /// Values mapped to `value`, in increasing order. There can be several
+    /// when shifted pieces overlap, or none when the value is never reached.
+    pub fn preimage(&self, value: usize) -> Vec<usize> {
+        (0..self.pieces.len())
+            .filter_map(|i| {
+                let candidate = value as i128 - self.pieces[i].offset;
+                self.domain(i)
+                    .contains(&candidate)
+                    .then_some(candidate as usize)
+            })
+            .collect()
+    }
+
+    /// Ranges of the values mapped inside the range, sorted and merged.
+    pub fn preimage_range(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut preimages: Vec<Range<usize>> = vec![];
+        for i in 0..self.pieces.len() {
+            let domain = self.domain(i);
+            let offset = self.pieces[i].offset;
+            let start = (range.start as i128 - offset).max(domain.start);
+            let end = (range.end as i128 - offset)
+                .min(domain.end)
+                .min(usize::MAX as i128);
+            if start >= end {
+                continue;
+            }
+            let (start, end) = (start as usize, end as usize);
+            match preimages.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => preimages.push(start..end),
+            }
+        }
+        preimages
+    }
+}
+
+impl Display for PiecewiseMap {
+    /// One piece per line, with the range it maps to.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let end = self.end(i);
+            let range = |offset| match end {
+                Some(end) => format!("{}..{}", shift(piece.start, offset), shift(end, offset)),
+                None => format!("{}..", shift(piece.start, offset)),
+            };
+            if piece.offset == 0 {
+                writeln!(f, "{} identity", range(0))?;
+            } else {
+                writeln!(
+                    f,
+                    "{} -> {} ({:+})",
+                    range(0),
+                    range(piece.offset),
+                    piece.offset
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed_to_soil() -> PiecewiseMap {
+        PiecewiseMap::from_conversions(&[(98..100, 50..52), (50..98, 52..100)])
+    }
+
+    #[test]
+    fn test_from_conversions() {
+        let map = seed_to_soil();
+        assert_eq!(
+            [0, 49, 50, 79, 97, 98, 99, 100].map(|s| map.apply(s)),
+            [0, 49, 52, 81, 99, 50, 51, 100]
+        );
+        assert_eq!(
+            map.to_string(),
+            "0..50 identity\n50..98 -> 52..100 (+2)\n98..100 -> 50..52 (-48)\n100.. identity\n"
+        );
+    }
+
+    #[test]
+    fn test_first_conversion_wins() {
+        let map = PiecewiseMap::from_conversions(&[(10..20, 110..120), (15..30, 215..230)]);
+        assert_eq!(
+            [14, 15, 19, 20, 29, 30].map(|v| map.apply(v)),
+            [114, 115, 119, 220, 229, 30]
+        );
+    }
+
+    #[test]
+    fn test_then() {
+        let soil_to_fertilizer =
+            PiecewiseMap::from_conversions(&[(15..52, 0..37), (52..54, 37..39), (0..15, 39..54)]);
+        let composed = seed_to_soil().then(&soil_to_fertilizer);
+        for seed in 0..120 {
+            assert_eq!(
+                composed.apply(seed),
+                soil_to_fertilizer.apply(seed_to_soil().apply(seed)),
+                "{seed}"
+            );
+        }
+        assert_eq!(composed.then(&PiecewiseMap::identity()), composed);
+    }
+
+    #[test]
+    fn test_preimage() {
+        let map = seed_to_soil();
+        assert_eq!(map.preimage(50), [98]);
+        assert_eq!(map.preimage(53), [51]);
+        assert_eq!(map.preimage(10), [10]);
+        let folded = PiecewiseMap::from_conversions(&[(0..5, 10..15)]);
+        assert_eq!(folded.preimage(12), [2, 12]);
+        assert!(folded.preimage(3).is_empty());
+        for soil in 0..110 {
+            let seeds: Vec<usize> = (0..200).filter(|&s| map.apply(s) == soil).collect();
+            assert_eq!(map.preimage(soil), seeds, "{soil}");
+        }
+    }
+
+    #[test]
+    fn test_preimage_range() {
+        let map = seed_to_soil();
+        assert_eq!(map.preimage_range(45..53), [45..51, 98..100]);
+        assert_eq!(map.preimage_range(99..102), [97..98, 100..102]);
+        assert!(map.preimage_range(7..7).is_empty());
+    }
+
+    #[test]
+    fn test_map_range() {
+        let map = seed_to_soil();
+        assert_eq!(map.map_range(40..99), [40..50, 52..100, 50..51]);
+        let unmapped = map.map_range(120..130);
+        assert_eq!((unmapped.len(), &unmapped[0]), (1, &(120..130)));
+        assert!(map.map_range(5..5).is_empty());
+    }
+}
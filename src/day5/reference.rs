@@ -0,0 +1,115 @@
+use std::fmt::Write;
+
+use super::{part1, part2, Almanac};
+use crate::rng::Rng;
+
+/// Categories of the generated almanacs, the chain uses a prefix of them.
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// Location of the seed, looking for the first matching row of every map
+/// one value at a time.
+fn location(almanac: &Almanac, seed: usize) -> usize {
+    almanac.maps.iter().fold(seed, |value, category| {
+        category
+            .conversions
+            .iter()
+            .find(|(source, _)| source.contains(&value))
+            .map_or(value, |(source, destination)| {
+                destination.start + (value - source.start)
+            })
+    })
+}
+
+/// Lowest location of part one and part two, trying every seed one by one.
+pub(super) fn lowest_locations(
+    almanac: &Almanac,
+) -> Result<(Option<usize>, Option<usize>), String> {
+    let part1 = almanac.seeds.iter().map(|&s| location(almanac, s)).min();
+    let part2 = almanac
+        .seed_ranges()?
+        .into_iter()
+        .flatten()
+        .map(|s| location(almanac, s))
+        .min();
+    Ok((part1, part2))
+}
+
+/// Random almanac in the puzzle syntax, values stay below `max` so the
+/// reference solver is fast. Source ranges of a map may overlap.
+pub(super) fn random_almanac(rng: &mut Rng, max: usize) -> String {
+    let seeds: Vec<String> = (0..rng.range(1..4))
+        .map(|_| format!("{} {}", rng.range(0..max), rng.range(1..max / 4 + 2)))
+        .collect();
+    let mut almanac = format!("seeds: {}\n", seeds.join(" "));
+    let maps = rng.range(1..CATEGORIES.len());
+    for pair in CATEGORIES[..=maps].windows(2) {
+        writeln!(almanac, "\n{}-to-{} map:", pair[0], pair[1]).unwrap();
+        for _ in 0..rng.range(0..6) {
+            let destination = rng.range(0..max);
+            let source = rng.range(0..max);
+            let length = rng.range(1..max / 2 + 2);
+            writeln!(almanac, "{destination} {source} {length}").unwrap();
+        }
+    }
+    almanac
+}
+
+/// Checks both solvers against the reference on `count` random almanacs,
+/// returning the first almanac they disagree on.
+pub(super) fn fuzz(rng: &mut Rng, count: usize, max: usize) -> Result<(), String> {
+    for _ in 0..count {
+        let input = random_almanac(rng, max);
+        let almanac: Almanac = input.parse()?;
+        let expected = lowest_locations(&almanac)?;
+        let found = (
+            part1::lowest_location(&almanac),
+            part2::lowest_location(&almanac)?,
+        );
+        if found != expected {
+            return Err(format!(
+                "Solvers found {found:?}, the reference {expected:?} on:\n{input}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_reference_example() {
+        let almanac: Almanac = fs::read_to_string("input/day5/example")
+            .expect("Need example file to test")
+            .parse()
+            .unwrap();
+        assert_eq!(lowest_locations(&almanac), Ok((Some(35), Some(46))));
+    }
+
+    #[test]
+    fn test_random_almanacs_parse() {
+        let mut rng = Rng::new(5);
+        for _ in 0..100 {
+            let input = random_almanac(&mut rng, 50);
+            assert!(input.parse::<Almanac>().is_ok(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_differential() {
+        let mut rng = Rng::new(49);
+        fuzz(&mut rng, 5000, 40).unwrap();
+        fuzz(&mut rng, 500, 1000).unwrap();
+    }
+}
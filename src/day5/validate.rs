@@ -0,0 +1,200 @@
+use std::{fmt::Display, ops::Range};
+
+use super::{conversion, parse_header, parse_row, parse_seeds, sections};
+
+/// Something in an almanac the solvers would reject or silently misread,
+/// lines start at 1.
+#[derive(Debug, PartialEq)]
+pub(super) enum Issue {
+    /// Line that cannot be parsed, with the reason.
+    Malformed { line: usize, reason: String },
+    /// Row converting no value at all.
+    ZeroLength { line: usize },
+    /// Row whose source or destination end does not fit in a `usize`.
+    Overflow { line: usize },
+    /// Source range of the row overlapping the one of an earlier row of the
+    /// same map, the earlier row wins.
+    Overlap {
+        line: usize,
+        earlier: usize,
+        values: Range<usize>,
+    },
+    /// Seeds cannot be read as the start and length pairs of part two.
+    OddSeedCount { count: usize },
+    /// Seed range of part two holding no seed at all.
+    EmptySeedRange { start: usize },
+    /// Seed range of part two whose end does not fit in a `usize`.
+    SeedOverflow { start: usize, length: usize },
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::Malformed { line, reason } => write!(f, "line {line} is malformed, {reason}"),
+            Issue::ZeroLength { line } => write!(f, "line {line} has a zero-length range"),
+            Issue::Overflow { line } => write!(f, "line {line} has a range ending past usize"),
+            Issue::Overlap {
+                line,
+                earlier,
+                values,
+            } => write!(
+                f,
+                "line {line} overlaps line {earlier} on sources {}..{}",
+                values.start, values.end
+            ),
+            Issue::OddSeedCount { count } => {
+                write!(f, "{count} seeds cannot be read as pairs in part two")
+            }
+            Issue::EmptySeedRange { start } => {
+                write!(f, "seed range {start} has a zero length")
+            }
+            Issue::SeedOverflow { start, length } => {
+                write!(f, "seed range {start} of length {length} ends past usize")
+            }
+        }
+    }
+}
+
+pub(super) fn validate(input: &str) -> Vec<Issue> {
+    let mut issues = vec![];
+    match input.lines().next().map(parse_seeds) {
+        Some(Ok(seeds)) if seeds.len() % 2 == 1 => {
+            issues.push(Issue::OddSeedCount { count: seeds.len() })
+        }
+        Some(Ok(seeds)) => {
+            for pair in seeds.chunks_exact(2) {
+                if pair[1] == 0 {
+                    issues.push(Issue::EmptySeedRange { start: pair[0] });
+                } else if pair[0].checked_add(pair[1]).is_none() {
+                    issues.push(Issue::SeedOverflow {
+                        start: pair[0],
+                        length: pair[1],
+                    });
+                }
+            }
+        }
+        Some(Err(reason)) => issues.push(Issue::Malformed { line: 1, reason }),
+        None => issues.push(Issue::Malformed {
+            line: 1,
+            reason: "expected the seeds".to_string(),
+        }),
+    }
+
+    for section in sections(input) {
+        let (line, header) = section[0];
+        if let Err(reason) = parse_header(header) {
+            issues.push(Issue::Malformed { line, reason });
+        }
+        let mut sources: Vec<(usize, Range<usize>)> = vec![];
+        for &(line, row) in section[1..].iter() {
+            let numbers = match parse_row(row) {
+                Ok(numbers) => numbers,
+                Err(reason) => {
+                    issues.push(Issue::Malformed { line, reason });
+                    continue;
+                }
+            };
+            if numbers[2] == 0 {
+                issues.push(Issue::ZeroLength { line });
+                continue;
+            }
+            let Some((source, _)) = conversion(numbers) else {
+                issues.push(Issue::Overflow { line });
+                continue;
+            };
+            for (earlier, other) in sources.iter() {
+                let values = source.start.max(other.start)..source.end.min(other.end);
+                if !values.is_empty() {
+                    issues.push(Issue::Overlap {
+                        line,
+                        earlier: *earlier,
+                        values,
+                    });
+                }
+            }
+            sources.push((line, source));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day5::Almanac;
+    use std::fs;
+
+    #[test]
+    fn test_example_is_valid() {
+        let input = fs::read_to_string("input/day5/example").expect("Need example file to test");
+        assert!(validate(&input).is_empty());
+    }
+
+    #[test]
+    fn test_issues() {
+        let input = "seeds: 1 2 3
+
+seed-to-soil map:
+50 98 2
+52 50 49
+7 7
+1 2 0
+5 18446744073709551615 1
+0 x 1
+
+soil to fertilizer:
+1 2 3
+";
+        assert_eq!(
+            validate(input),
+            [
+                Issue::OddSeedCount { count: 3 },
+                Issue::Overlap {
+                    line: 5,
+                    earlier: 4,
+                    values: 98..99
+                },
+                Issue::Malformed {
+                    line: 6,
+                    reason: "expected 3 numbers, found 2".to_string()
+                },
+                Issue::ZeroLength { line: 7 },
+                Issue::Overflow { line: 8 },
+                Issue::Malformed {
+                    line: 9,
+                    reason: "invalid number `x`".to_string()
+                },
+                Issue::Malformed {
+                    line: 11,
+                    reason: "Invalid map header `soil to fertilizer:`".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            input.parse::<Almanac>().unwrap_err(),
+            "Line 6: expected 3 numbers, found 2"
+        );
+    }
+
+    #[test]
+    fn test_seed_ranges() {
+        let input = format!("seeds: {} 1 5 {} 7 0", usize::MAX, usize::MAX - 5);
+        assert_eq!(
+            validate(&input),
+            [
+                Issue::SeedOverflow {
+                    start: usize::MAX,
+                    length: 1
+                },
+                Issue::EmptySeedRange { start: 7 }
+            ]
+        );
+        assert_eq!(
+            validate("seeds:1 2"),
+            [Issue::Malformed {
+                line: 1,
+                reason: "expected `seeds: <numbers>`".to_string()
+            }]
+        );
+    }
+}
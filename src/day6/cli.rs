@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+use super::{solver::verify, *};
+use crate::{cli::Args, rng::Rng};
+
+const USAGE: &str = "Usage: day6 <command> [--input FILE]
+Commands:
+  solve [--solver closed-form|scan]
+      Answers both parts with their timing, with the closed form by
+      default or the scan of every charge time.
+  verify [--count N] [--seed S] [--max-time N]
+      Compares the closed form with the scan on N random races (10000 by
+      default) shorter than max-time (1000 by default).";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args = Args::parse(args, &[], &["input", "solver", "count", "seed", "max-time"])?;
+    match args.positional() {
+        [command] if command == "solve" => solve(&args),
+        [command] if command == "verify" => run_verify(&args),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn solve(args: &Args) -> Result<(), String> {
+    let solver: Solver = args.value("solver").unwrap_or("closed-form").parse()?;
+    let input = args.input(6)?;
+    let races = parse_races(&input).ok_or("Invalid races")?;
+    let race = parse_correct_race(&input).ok_or("Invalid races")?;
+
+    let start = Instant::now();
+    let product: usize = races
+        .iter()
+        .map(|race| solver.winning_charges(race.time, race.distance))
+        .product();
+    println!("Part one: {product} ({}us)", start.elapsed().as_micros());
+    let start = Instant::now();
+    let ways = solver.winning_charges(race.time, race.distance);
+    println!("Part two: {ways} ({}us)", start.elapsed().as_micros());
+    Ok(())
+}
+
+fn run_verify(args: &Args) -> Result<(), String> {
+    let count = args.parsed("count")?.unwrap_or(10_000);
+    let max_time: usize = args.parsed("max-time")?.unwrap_or(1000);
+    if max_time == 0 {
+        return Err("--max-time must be positive".to_string());
+    }
+    let mut rng = Rng::new(args.parsed("seed")?.unwrap_or(0));
+    verify(&mut rng, count, max_time)?;
+    println!("Closed form agrees with the scan on {count} races");
+    Ok(())
+}
@@ -1,3 +1,8 @@
+pub(crate) mod cli;
+mod solver;
+
+use solver::Solver;
+
 #[derive(Debug)]
 struct Race {
     time: usize,
@@ -48,11 +53,7 @@ pub(crate) mod part1 {
 
         races
             .into_iter()
-            .map(|race| {
-                let winning = |charge: &usize| (charge * (race.time - charge)) > race.distance;
-                let first_time = (0..race.time).find(winning).unwrap();
-                race.time - first_time * 2 + 1
-            })
+            .map(|race| Solver::ClosedForm.winning_charges(race.time, race.distance))
             .product::<usize>()
             .to_string()
     }
@@ -78,13 +79,9 @@ pub(crate) mod part2 {
 
     pub fn resolve(input: &str) -> String {
         let race = parse_correct_race(input).expect("Expected valid input");
-        let winning = |charge: &usize| (charge * (race.time - charge)) > race.distance;
-        let first_time = (0..race.time)
-            .find(winning)
-            .expect("Expected a winnable race");
-        let possibilities = race.time - first_time * 2 + 1;
-
-        possibilities.to_string()
+        Solver::ClosedForm
+            .winning_charges(race.time, race.distance)
+            .to_string()
     }
 
     #[cfg(test)]
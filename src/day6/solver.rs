@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use crate::rng::Rng;
+
+/// How the winning charge times of a race are counted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Solver {
+    /// Roots of the quadratic equation, with an exact integer square root.
+    ClosedForm,
+    /// Tries every charge time until the first winning one, kept as a
+    /// reference.
+    Scan,
+}
+
+impl FromStr for Solver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "closed-form" => Ok(Solver::ClosedForm),
+            "scan" => Ok(Solver::Scan),
+            _ => Err(format!(
+                "Unknown solver `{s}`, expected closed-form or scan"
+            )),
+        }
+    }
+}
+
+impl Solver {
+    /// Amount of charge times going further than `distance` in `time`.
+    pub fn winning_charges(self, time: usize, distance: usize) -> usize {
+        match self {
+            Solver::ClosedForm => closed_form(time, distance),
+            Solver::Scan => scan(time, distance),
+        }
+    }
+}
+
+fn scan(time: usize, distance: usize) -> usize {
+    let winning = |charge: &usize| (charge * (time - charge)) > distance;
+    (0..time)
+        .find(winning)
+        .map_or(0, |first_time| time - first_time * 2 + 1)
+}
+
+/// Charging `c` wins when `c * (time - c) > distance`, so between the roots
+/// of `c² - time * c + distance`. Winners are symmetric around `time / 2`,
+/// only the first one is needed.
+fn closed_form(time: usize, distance: usize) -> usize {
+    let (time, distance) = (time as u128, distance as u128);
+    let Some(discriminant) = (time * time).checked_sub(4 * distance) else {
+        return 0;
+    };
+    let winning = |charge: u128| charge * (time - charge) > distance;
+    // The root is within one of the exact one, step to the first winner
+    let mut first = (time - discriminant.isqrt()) / 2;
+    while first > 0 && winning(first - 1) {
+        first -= 1;
+    }
+    while first <= time / 2 && !winning(first) {
+        first += 1;
+    }
+    if first > time / 2 {
+        return 0;
+    }
+    (time - 2 * first + 1) as usize
+}
+
+/// Compares both solvers on `count` random races shorter than `max_time`,
+/// returning the first race they disagree on.
+pub(super) fn verify(rng: &mut Rng, count: usize, max_time: usize) -> Result<(), String> {
+    for _ in 0..count {
+        let time = rng.range(0..max_time);
+        // Mostly winnable races, with some out of reach
+        let distance = rng.range(0..time * time / 4 + 3);
+        let closed_form = Solver::ClosedForm.winning_charges(time, distance);
+        let scan = Solver::Scan.winning_charges(time, distance);
+        if closed_form != scan {
+            return Err(format!(
+                "Race of {time}ms to beat {distance}mm: closed form finds {closed_form}, scan {scan}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundaries() {
+        // Roots 3 and 7 are exact, neither wins
+        assert_eq!(closed_form(10, 21), 3);
+        // Only a tie at the top
+        assert_eq!(closed_form(10, 25), 0);
+        assert_eq!(closed_form(10, 24), 1);
+        assert_eq!(closed_form(0, 0), 0);
+        assert_eq!(closed_form(1, 0), 0);
+        assert_eq!(closed_form(2, 0), 1);
+    }
+
+    #[test]
+    fn test_against_scan() {
+        verify(&mut Rng::new(6), 5000, 300).unwrap();
+    }
+
+    #[test]
+    fn test_large_races() {
+        // Too large for a scan or for f64, check the first winner instead
+        let mut rng = Rng::new(60);
+        for _ in 0..1000 {
+            let time = (1 << 31) + rng.range(0..1 << 30);
+            let distance = (time / 2) * (time - time / 2) - rng.range(1..1 << 20);
+            let count = closed_form(time, distance);
+            let first = (time - count).div_ceil(2);
+            let winning = |c: usize| c as u128 * (time - c) as u128 > distance as u128;
+            assert!(winning(first) && !winning(first - 1), "{time} {distance}");
+        }
+    }
+}
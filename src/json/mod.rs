@@ -0,0 +1,424 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A JSON document, objects keep the order of their keys.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn object<K: Into<String>>(fields: impl IntoIterator<Item = (K, Value)>) -> Self {
+        Value::Object(fields.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Value::Int(n) => (*n).try_into().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Int(value as i128)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl Display for Value {
+    /// Compact JSON, without any whitespace.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(n) => write!(f, "{n}"),
+            Value::Float(n) if n.is_finite() => write!(f, "{n:?}"),
+            Value::Float(_) => write!(f, "null"),
+            Value::String(s) => write_string(f, s),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Whether `s` follows the JSON number grammar, which is stricter than the
+/// Rust parsers: no `+` sign, no leading zero, and digits on both sides of
+/// the `.` and after the exponent.
+fn is_number(s: &str) -> bool {
+    let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let s = s.strip_prefix('-').unwrap_or(s);
+    let int = digits(s);
+    if int == 0 || (int > 1 && s.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &s[int..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = digits(fraction);
+        if len == 0 {
+            return false;
+        }
+        rest = &fraction[len..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let len = digits(exponent);
+        if len == 0 {
+            return false;
+        }
+        rest = &exponent[len..];
+    }
+    rest.is_empty()
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        Err(format!("{message} at byte {}", self.position))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.next_char() {
+            Some(c) if c == expected => Ok(()),
+            _ => self.error(&format!("Expected `{expected}`")),
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, String> {
+        if self.input[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            Ok(value)
+        } else {
+            self.error("Unknown literal")
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.literal("null", Value::Null),
+            Some('t') => self.literal("true", Value::Bool(true)),
+            Some('f') => self.literal("false", Value::Bool(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => self.error("Unexpected character"),
+            None => self.error("Unexpected end of input"),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let rest = &self.input[self.position..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or(rest.len());
+        let number = &rest[..len];
+        if !is_number(number) {
+            return self.error("Invalid number");
+        }
+        let value = if number.contains(['.', 'e', 'E']) {
+            number.parse().map(Value::Float).ok()
+        } else {
+            number.parse().map(Value::Int).ok()
+        };
+        match value {
+            Some(value) => {
+                self.position += len;
+                Ok(value)
+            }
+            None => self.error("Invalid number"),
+        }
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, String> {
+        let hex = self.input.get(self.position..self.position + 4);
+        let hex = hex.filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()));
+        match hex.and_then(|h| u32::from_str_radix(h, 16).ok()) {
+            Some(code) => {
+                self.position += 4;
+                Ok(code)
+            }
+            None => self.error("Invalid unicode escape"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next_char() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next_char() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        let mut code = self.hex_escape()?;
+                        if (0xDC00..0xE000).contains(&code) {
+                            return self.error("Unpaired surrogate");
+                        }
+                        if (0xD800..0xDC00).contains(&code) {
+                            if !self.input[self.position..].starts_with("\\u") {
+                                return self.error("Unpaired surrogate");
+                            }
+                            self.position += 2;
+                            let low = self.hex_escape()?;
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return self.error("Unpaired surrogate");
+                            }
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                        }
+                        match char::from_u32(code) {
+                            Some(c) => s.push(c),
+                            None => return self.error("Invalid unicode escape"),
+                        }
+                    }
+                    _ => return self.error("Invalid escape"),
+                },
+                Some(c) if (c as u32) < 0x20 => return self.error("Control character in string"),
+                Some(c) => s.push(c),
+                None => return self.error("Unterminated string"),
+            }
+        }
+    }
+
+    /// Parses the comma separated items between `open` and `close`.
+    fn sequence(
+        &mut self,
+        open: char,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.expect(open)?;
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.position += 1;
+            return Ok(());
+        }
+        loop {
+            item(self)?;
+            self.skip_whitespace();
+            match self.next_char() {
+                Some(',') => {}
+                Some(c) if c == close => return Ok(()),
+                _ => return self.error(&format!("Expected `,` or `{close}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        let mut values = vec![];
+        self.sequence('[', ']', |p| {
+            values.push(p.value()?);
+            Ok(())
+        })?;
+        Ok(Value::Array(values))
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        let mut fields = vec![];
+        self.sequence('{', '}', |p| {
+            p.skip_whitespace();
+            let key = p.string()?;
+            p.expect(':')?;
+            fields.push((key, p.value()?));
+            Ok(())
+        })?;
+        Ok(Value::Object(fields))
+    }
+}
+
+impl FromStr for Value {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input: s,
+            position: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position != s.len() {
+            return parser.error("Trailing characters");
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let value = Value::object([
+            ("id", Value::from(3)),
+            ("name", Value::from("a \"quoted\"\n\u{1}é")),
+            (
+                "list",
+                Value::Array(vec![
+                    Value::Null,
+                    Value::Bool(true),
+                    Value::Int(-4),
+                    Value::Float(0.5),
+                ]),
+            ),
+            ("empty", Value::Object(vec![])),
+        ]);
+        let text = value.to_string();
+        assert_eq!(
+            text,
+            r#"{"id":3,"name":"a \"quoted\"\n\u0001é","list":[null,true,-4,0.5],"empty":{}}"#
+        );
+        assert_eq!(text.parse::<Value>().unwrap(), value);
+    }
+
+    #[test]
+    fn test_parse_whitespace_and_escapes() {
+        let value: Value = " { \"a\" : [ 1 , 2 ] , \"b\" : \"\\u00e9\\ud83d\\ude00\" } "
+            .parse()
+            .unwrap();
+        assert_eq!(value.get("a").and_then(Value::as_array).unwrap().len(), 2);
+        assert_eq!(value.get("b").and_then(Value::as_str), Some("é😀"));
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        for (text, value) in [
+            ("0", Value::Int(0)),
+            ("-0", Value::Int(0)),
+            ("120", Value::Int(120)),
+            ("0.25", Value::Float(0.25)),
+            ("-1.5e-3", Value::Float(-0.0015)),
+            ("2E+2", Value::Float(200.0)),
+        ] {
+            assert_eq!(text.parse::<Value>(), Ok(value), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for invalid in [
+            "",
+            "[1,]",
+            "{\"a\" 1}",
+            "\"open",
+            "[1] 2",
+            "nul",
+            "01x",
+            r#""\ud800\u0041""#,
+            r#""\udc00""#,
+            r#""\u+041""#,
+            "007",
+            "-01",
+            "1.",
+            ".5",
+            "1.e3",
+            "1e",
+            "1e+",
+            "+1",
+            "--1",
+        ] {
+            assert!(invalid.parse::<Value>().is_err(), "{invalid}");
+        }
+    }
+}
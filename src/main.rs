@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+mod cli;
 mod day1;
 mod day2;
 mod day3;
@@ -7,6 +8,9 @@ mod day4;
 mod day5;
 mod day6;
 mod day7;
+mod json;
+mod range_set;
+mod rng;
 
 macro_rules! day {
     ($name:expr, $day:ident) => {
@@ -19,6 +23,30 @@ macro_rules! day {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, args)) = args.split_first() else {
+        run_all();
+        return;
+    };
+
+    let result = match command.as_str() {
+        "day1" => day1::cli::run(args),
+        "day2" => day2::cli::run(args),
+        "day3" => day3::cli::run(args),
+        "day4" => day4::cli::run(args),
+        "day5" => day5::cli::run(args),
+        "day6" => day6::cli::run(args),
+        _ => Err(format!(
+            "Unknown command {command}, run without arguments to solve every day"
+        )),
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+fn run_all() {
     type SolverFn = fn(&str) -> Box<dyn Display>;
 
     use std::fs;
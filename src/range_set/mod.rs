@@ -0,0 +1,174 @@
+use std::ops::Range;
+
+/// Set of values stored as half-open ranges, kept sorted and merged: ranges
+/// are never empty and never overlap nor touch each other.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RangeSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Ord + Copy> RangeSet<T> {
+    pub fn new() -> Self {
+        RangeSet { ranges: vec![] }
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    /// Smallest value of the set.
+    pub fn first(&self) -> Option<T> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    /// Adds the range, merging it with the ranges it overlaps or touches.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = match self.ranges[first..last] {
+            [] => range,
+            ref touched => {
+                touched[0].start.min(range.start)..touched[touched.len() - 1].end.max(range.end)
+            }
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut union = self.clone();
+        for range in other.ranges.iter() {
+            union.insert(range.clone());
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let (start, end) = (a.start.max(b.start), a.end.min(b.end));
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// Values of `self` that are not in `other`.
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut ranges = vec![];
+        let mut j = 0;
+        for range in self.ranges.iter() {
+            while other.ranges.get(j).is_some_and(|r| r.end <= range.start) {
+                j += 1;
+            }
+            let mut start = range.start;
+            for removed in other.ranges[j..].iter().take_while(|r| r.start < range.end) {
+                if start < removed.start {
+                    ranges.push(start..removed.start);
+                }
+                start = start.max(removed.end);
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// Values of the universe that are not in the set.
+    pub fn complement(&self, universe: Range<T>) -> RangeSet<T> {
+        RangeSet::from(universe).difference(self)
+    }
+}
+
+impl<T: Ord + Copy> From<Range<T>> for RangeSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = RangeSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Values below 64 as a bitmask, to check the operations against.
+    fn mask(set: &RangeSet<u32>) -> u64 {
+        set.ranges()
+            .iter()
+            .flat_map(|r| r.clone())
+            .fold(0, |mask, v| mask | 1 << v)
+    }
+
+    fn random_set(rng: &mut Rng) -> RangeSet<u32> {
+        (0..rng.range(0..6))
+            .map(|_| {
+                let start = rng.range(0..64) as u32;
+                start..(start + rng.range(0..12) as u32).min(64)
+            })
+            .collect()
+    }
+
+    fn assert_normalized(set: &RangeSet<u32>) {
+        assert!(set.ranges().iter().all(|r| !r.is_empty()), "{set:?}");
+        assert!(
+            set.ranges().windows(2).all(|w| w[0].end < w[1].start),
+            "{set:?}"
+        );
+    }
+
+    #[test]
+    fn test_insert_merges_touching_ranges() {
+        let set: RangeSet<u32> = [5..10, 0..5, 12..12, 20..30, 11..21].into_iter().collect();
+        assert_eq!(set.ranges(), [0..10, 11..30]);
+        assert_eq!(set.first(), Some(0));
+        assert_eq!(RangeSet::<u32>::new().first(), None);
+    }
+
+    #[test]
+    fn test_touching_ranges_do_not_intersect() {
+        let a = RangeSet::from(0..5);
+        let b = RangeSet::from(5..10);
+        assert!(a.intersection(&b).ranges().is_empty());
+        assert_eq!(a.difference(&b), a);
+    }
+
+    #[test]
+    fn test_operations_against_bitmasks() {
+        let mut rng = Rng::new(47);
+        for _ in 0..2000 {
+            let (a, b) = (random_set(&mut rng), random_set(&mut rng));
+            let results = [
+                (a.union(&b), mask(&a) | mask(&b)),
+                (a.intersection(&b), mask(&a) & mask(&b)),
+                (a.difference(&b), mask(&a) & !mask(&b)),
+                (a.complement(0..64), !mask(&a)),
+            ];
+            for (set, expected) in results {
+                assert_normalized(&set);
+                assert_eq!(mask(&set), expected, "{a:?} {b:?} {set:?}");
+            }
+        }
+    }
+}
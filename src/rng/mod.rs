@@ -0,0 +1,41 @@
+use std::ops::Range;
+
+/// Small xorshift generator for the random inputs of tests and tools, the
+/// same seed always gives the same sequence.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift, so mix the seed first
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Uniform value inside the range, which must not be empty.
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "Cannot pick from an empty range");
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_bounds() {
+        let mut rng = Rng::new(0);
+        for _ in 0..1000 {
+            assert!((3..7).contains(&rng.range(3..7)));
+        }
+        assert_eq!(Rng::new(42).next_u64(), Rng::new(42).next_u64());
+    }
+}